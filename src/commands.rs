@@ -1,31 +1,91 @@
-use crate::{enums::Enums, stationpedia::Stationpedia};
+use crate::{config::Config, enums::Enums, stationpedia::Stationpedia};
 
 use clap::Subcommand;
 
+pub mod character;
+#[cfg(feature = "mw")]
+pub mod check;
+pub mod config;
+pub mod constants;
+pub mod cost;
+pub mod diff;
+pub mod enums;
+pub mod fields;
 pub mod instructions;
+pub mod logic_types;
+pub mod memory;
+pub mod merge;
+#[cfg(feature = "mw")]
+pub mod publish;
 #[cfg(feature = "mw")]
 pub mod query;
+pub mod reagent;
 pub mod wikibox;
 
 #[derive(Debug, Subcommand)]
 pub enum Sub {
+    Character(character::Character),
+    #[cfg(feature = "mw")]
+    Check(check::Check),
+    Config(config::ConfigCommand),
+    Constants(constants::Constants),
+    Cost(cost::Cost),
+    Decode(memory::Decode),
+    Diff(diff::Diff),
+    Encode(memory::Encode),
+    Enums(enums::EnumsPage),
+    Fields(fields::Fields),
     Instructions(instructions::Instructions),
+    LogicTypes(logic_types::LogicTypesReport),
+    Merge(merge::Merge),
+    Reagent(reagent::ReagentPage),
     Wikibox(wikibox::Wikibox),
     #[cfg(feature = "mw")]
+    Publish(publish::Publish),
+    #[cfg(feature = "mw")]
     Query(query::Query),
 }
 
+/// Hidden comment put at the top of generated output, naming the game and tool version it came from.
+pub fn provenance(stationpedia: &Stationpedia) -> String {
+    let game = match &stationpedia.version {
+        Some(version) => format!(" from Stationeers {version}"),
+        None => String::new(),
+    };
+    format!(
+        "<!-- Generated by {} {}{game} -->\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
 impl super::Cli {
     pub fn run_subcommand(
         &self,
         stationpedia: &Stationpedia,
         enums: &Enums,
-        config: &toml_edit::DocumentMut,
+        config: &Config,
     ) -> color_eyre::Result<()> {
         match &self.subcommand {
+            Sub::Character(c) => c.run(stationpedia, enums, config, self.verbose)?,
+            #[cfg(feature = "mw")]
+            Sub::Check(c) => c.run(stationpedia, enums, config, self.verbose)?,
+            Sub::Config(c) => c.run(stationpedia, enums, config, self.verbose)?,
+            Sub::Constants(c) => c.run(stationpedia, enums, self.verbose)?,
+            Sub::Cost(c) => c.run(stationpedia, enums, self.verbose)?,
+            Sub::Decode(c) => c.run(stationpedia, enums, self.verbose)?,
+            Sub::Diff(c) => c.run(stationpedia, enums, self.verbose)?,
+            Sub::Encode(c) => c.run(stationpedia, enums, self.verbose)?,
+            Sub::Enums(c) => c.run(stationpedia, enums, config, self.verbose)?,
+            Sub::Fields(c) => c.run(stationpedia, enums, self.verbose)?,
             Sub::Instructions(c) => c.run(stationpedia, enums, config, self.verbose)?,
+            Sub::LogicTypes(c) => c.run(stationpedia, enums, config, self.verbose)?,
+            Sub::Merge(c) => c.run(stationpedia, enums, self.verbose)?,
+            Sub::Reagent(c) => c.run(stationpedia, enums, config, self.verbose)?,
             Sub::Wikibox(c) => c.run(stationpedia, enums, config, self.verbose)?,
             #[cfg(feature = "mw")]
+            Sub::Publish(c) => c.run(stationpedia, config, self.verbose)?,
+            #[cfg(feature = "mw")]
             Sub::Query(c) => c.run(stationpedia, config, self.verbose)?,
         }
         Ok(())
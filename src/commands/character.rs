@@ -0,0 +1,243 @@
+//! Generates reference tables for the player character and its organs, from `Stationpedia.core_prefabs`.
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use color_eyre::eyre;
+
+use crate::{
+    config::Config,
+    enums::Enums,
+    stationpedia::{CorePrefab, HumanPrefab, Stationpedia},
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct Character {
+    /// Only render this core prefab, e.g. `Character` or `OrganLungsZrilian`
+    prefab: Option<String>,
+    /// Write to this file instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Wiki page name of a core prefab, from `character.names` in the config or the prefab name itself.
+fn core_title<'a>(config: &'a Config, name: &'a str) -> &'a str {
+    config
+        .character
+        .names
+        .get(name)
+        .map_or(name, String::as_str)
+}
+
+/// Wiki link to a gas, using `stationpedia.links` like the `<link=GasOxygen>` tags in descriptions do.
+fn gas_link(config: &Config, gas: &str) -> String {
+    match config.link(&format!("Gas{gas}")) {
+        Some(link) if link != gas => format!("[[{link}|{gas}]]"),
+        _ => format!("[[{gas}]]"),
+    }
+}
+
+fn human_tables(
+    config: &Config,
+    prefab: &CorePrefab,
+    human: &HumanPrefab,
+    organs: &[&CorePrefab],
+) -> color_eyre::Result<String> {
+    let mut out = String::new();
+    writeln!(out, "== {} ==", core_title(config, &prefab.name))?;
+    let organs = organs
+        .iter()
+        .map(|o| format!("[[{}]]", core_title(config, &o.name)))
+        .collect::<Vec<_>>();
+    if !organs.is_empty() {
+        writeln!(out, "Organs: {}\n", organs.join(", "))?;
+    }
+
+    out.push_str("=== Thresholds ===\n");
+    out.push_str("{| class=\"wikitable\"\n! Stat !! Warning !! Critical !! Maximum !! Storage\n");
+    // storage isn't in the units of the thresholds, so it gets a column of its own
+    let thresholds = [
+        (
+            "Oxygen",
+            human.warning_oxygen,
+            human.critical_oxygen,
+            None,
+            Some(human.max_oxygen_storage),
+        ),
+        (
+            "Nutrition",
+            human.warning_nutrition,
+            human.critical_nutrition,
+            Some(human.full_nutrition),
+            Some(human.base_nutrition_storage),
+        ),
+        (
+            "Hydration",
+            human.warning_hydration,
+            human.critical_hydration,
+            Some(human.max_hydration),
+            None,
+        ),
+        (
+            "Health",
+            human.warning_health,
+            human.critical_health,
+            None,
+            None,
+        ),
+        (
+            "Mood",
+            human.warning_mood,
+            human.critical_mood,
+            Some(human.max_mood),
+            None,
+        ),
+        (
+            "Hygiene",
+            human.warning_hygiene,
+            human.critical_hygiene,
+            Some(human.max_hygiene),
+            None,
+        ),
+    ];
+    let or_dash = |v: Option<f64>| v.map_or_else(|| "-".to_owned(), |v| v.to_string());
+    for (stat, warning, critical, max, storage) in thresholds {
+        writeln!(
+            out,
+            "|-\n| {stat} || {warning} || {critical} || {} || {}",
+            or_dash(max),
+            or_dash(storage)
+        )?;
+    }
+    out.push_str("|}\n");
+    // these are the state of a character, not a property of it, and always 0 on the prefab
+    out.push_str(
+        "<!-- Not shown: Hydration, Nutrition, Oxygenation, Mood and Hygiene, the current values of a character, and \
+         Slots -->\n",
+    );
+
+    out.push_str("\n=== Damage rates ===\n");
+    out.push_str("{| class=\"wikitable\"\n! Cause !! Awake !! Sleeping\n");
+    writeln!(
+        out,
+        "|-\n| Starvation || {} || {}",
+        human.nutrition_damage_rate_awake, human.nutrition_damage_rate_sleeping
+    )?;
+    writeln!(
+        out,
+        "|-\n| Dehydration || {} || {}",
+        human.dehydration_damage_rate_awake, human.dehydration_damage_rate_sleeping
+    )?;
+    out.push_str("|}\n");
+
+    out.push_str("\n=== Food ===\n");
+    out.push_str("{| class=\"wikitable\"\n");
+    writeln!(out, "|-\n! Food quality\n| {}", human.food_quality)?;
+    writeln!(
+        out,
+        "|-\n! Maximum food quality\n| {}",
+        human.max_food_quality
+    )?;
+    out.push_str("|}\n");
+    Ok(out)
+}
+
+fn lungs_table(
+    config: &Config,
+    lungs: &[&CorePrefab],
+    human: Option<&HumanPrefab>,
+) -> color_eyre::Result<String> {
+    let mut out = String::new();
+    out.push_str("== Lungs ==\n");
+    if let Some(human) = human {
+        writeln!(
+            out,
+            "Toxic gases give a warning above a partial pressure of {} kPa and cause damage above {} kPa.\n",
+            human.toxic_partial_pressure_warning, human.toxic_partial_pressure_damage
+        )?;
+    }
+    out.push_str("{| class=\"wikitable sortable\"\n");
+    out.push_str("! Lungs !! Breathes !! Toxic !! Temperature (K) !! Volume (L)\n");
+    for prefab in lungs {
+        let Some(l) = &prefab.lungs else {
+            continue;
+        };
+        let toxic = l
+            .toxic_types
+            .iter()
+            .map(|gas| gas_link(config, gas))
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "|-\n| [[{}]] || {} || {} || {}–{} || {}",
+            core_title(config, &prefab.name),
+            gas_link(config, &l.breathable_type),
+            toxic.join(", "),
+            l.temperature_min,
+            l.temperature_max,
+            l.volume
+        )?;
+    }
+    out.push_str("|}\n");
+    Ok(out)
+}
+
+impl Character {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        _enums: &Enums,
+        config: &Config,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let selected = match &self.prefab {
+            Some(name) => {
+                let prefab = stationpedia
+                    .core_prefabs
+                    .iter()
+                    .find(|p| {
+                        p.name.eq_ignore_ascii_case(name)
+                            || core_title(config, &p.name).eq_ignore_ascii_case(name)
+                    })
+                    .ok_or_else(|| eyre::eyre!("no core prefab named `{name}`"))?;
+                vec![prefab]
+            }
+            None => stationpedia.core_prefabs.iter().collect(),
+        };
+        let organs = stationpedia
+            .core_prefabs
+            .iter()
+            .filter(|p| p.organ.is_some())
+            .collect::<Vec<_>>();
+        // the toxic thresholds are on the character, but apply to every kind of lungs
+        let character = stationpedia
+            .core_prefabs
+            .iter()
+            .find_map(|p| p.human.as_ref());
+
+        let mut out = String::new();
+        for prefab in &selected {
+            if let Some(human) = &prefab.human {
+                out.push('\n');
+                out.push_str(&human_tables(config, prefab, human, &organs)?);
+            }
+        }
+        let lungs = selected
+            .iter()
+            .copied()
+            .filter(|p| p.lungs.is_some())
+            .collect::<Vec<_>>();
+        if !lungs.is_empty() {
+            out.push('\n');
+            out.push_str(&lungs_table(config, &lungs, character)?);
+        }
+        if out.is_empty() {
+            eyre::bail!("nothing to render for the selected core prefabs");
+        }
+        let out = super::provenance(stationpedia) + &out;
+        match &self.out {
+            Some(path) => std::fs::write(path, out)?,
+            None => print!("{out}"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+#![cfg(feature = "mw")]
+//! Compare the generated templates against the pages on the wiki
+
+use std::{fmt, path::PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{
+    config::Config,
+    diagnostics::Diagnostics,
+    enums::Enums,
+    stationpedia::{GameVersion, Page, Stationpedia},
+    wiki::Wiki,
+    wikitext,
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct Check {
+    /// Key, title or prefab name of the pages to check
+    #[arg(required_unless_present = "filter")]
+    items: Vec<String>,
+    /// Check every page where the key, title or prefab name matches this regex
+    #[arg(long)]
+    filter: Option<regex::Regex>,
+    /// API endpoint to use instead of `[bot] api_url`, e.g. a local test wiki
+    #[arg(long)]
+    api_url: Option<String>,
+    /// Read the pages from `<dir>/<Title>.wiki` instead of the wiki, a missing file is a missing page
+    #[arg(long, conflicts_with_all = ["api_url", "record"])]
+    recorded: Option<PathBuf>,
+    /// Also save the fetched pages to `<dir>/<Title>.wiki`, for later use with `--recorded`
+    #[arg(long)]
+    record: Option<PathBuf>,
+    /// Only list the pages, without the diffs
+    #[arg(long)]
+    quiet: bool,
+}
+
+/// Why a page doesn't match the generated output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    /// The page, or one of the generated templates on it, doesn't exist
+    Missing,
+    /// Generated from another game version
+    Stale,
+    /// Differs without being generated from another game version
+    HandEdited,
+    /// Nothing is generated for the page that could be compared, doesn't fail the check
+    NotChecked,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Status::Missing => "missing",
+            Status::Stale => "stale",
+            Status::HandEdited => "hand-edited",
+            Status::NotChecked => "not checked",
+        })
+    }
+}
+
+/// Where the current text of the pages comes from.
+enum Source {
+    Wiki(Wiki),
+    Recorded(PathBuf),
+}
+
+impl Source {
+    async fn wikitext(&self, title: &str) -> color_eyre::Result<Option<String>> {
+        match self {
+            Source::Wiki(wiki) => wiki.wikitext(title).await,
+            Source::Recorded(dir) => {
+                let path = dir.join(file_name(title));
+                match std::fs::read_to_string(&path) {
+                    Ok(text) => Ok(Some(text)),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e).wrap_err_with(|| format!("couldn't read {}", path.display())),
+                }
+            }
+        }
+    }
+}
+
+/// File name for a page, escaping `/` like `wikibox --all` does.
+fn file_name(title: &str) -> String {
+    format!("{}.wiki", title.replace('/', "%2F"))
+}
+
+/// Game version named in the [`super::provenance`] comment of `text`, if it has one.
+fn provenance_version(text: &str) -> Option<GameVersion> {
+    let start = text.find("<!-- Generated by ")?;
+    let comment = &text[start..];
+    let comment = &comment[..comment.find(" -->")?];
+    comment
+        .rsplit_once(" from Stationeers ")
+        .and_then(|(_, version)| version.parse().ok())
+}
+
+/// Line diff of `old` to `new` in unified format with three lines of context, empty if they're the same.
+fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    const CONTEXT: usize = 3;
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    // (' ' | '-' | '+', line in old, line in new)
+    let mut ops = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', i, j));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', i, j));
+            i += 1;
+        } else {
+            ops.push(('+', i, j));
+            j += 1;
+        }
+    }
+
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, ..))| *op != ' ')
+        .map(|(n, _)| n)
+        .collect::<Vec<_>>();
+    let Some(&first) = changes.first() else {
+        return String::new();
+    };
+    let mut out = format!("--- {old_name}\n+++ {new_name}\n");
+    let mut hunk = (first.saturating_sub(CONTEXT), first);
+    let mut hunks = vec![];
+    for &change in &changes[1..] {
+        if change - hunk.1 > 2 * CONTEXT {
+            hunks.push(hunk);
+            hunk = (change - CONTEXT, change);
+        } else {
+            hunk.1 = change;
+        }
+    }
+    hunks.push(hunk);
+    for (start, last) in hunks {
+        let ops = &ops[start..=(last + CONTEXT).min(ops.len() - 1)];
+        let old_len = ops.iter().filter(|(op, ..)| *op != '+').count();
+        let new_len = ops.iter().filter(|(op, ..)| *op != '-').count();
+        let (_, old_start, new_start) = ops[0];
+        // an empty range is numbered by the line before it
+        let old_start = if old_len == 0 {
+            old_start
+        } else {
+            old_start + 1
+        };
+        let new_start = if new_len == 0 {
+            new_start
+        } else {
+            new_start + 1
+        };
+        out.push_str(&format!(
+            "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+        ));
+        for &(op, i, j) in ops {
+            let line = if op == '+' { new[j] } else { old[i] };
+            out.push_str(&format!("{op}{line}\n"));
+        }
+    }
+    out
+}
+
+impl Check {
+    /// The pages to check, by name or filter.
+    fn pages<'a>(&self, pedia: &'a Stationpedia) -> color_eyre::Result<Vec<&'a Page>> {
+        let mut pages = vec![];
+        for item in &self.items {
+            let page = pedia
+                .lookup_prefab_name(item)
+                .or_else(|| pedia.lookup_key(item))
+                .or_else(|| pedia.lookup_title_ignore_case(item))
+                .ok_or_else(|| eyre::eyre!("no page found for `{item}`"))?;
+            pages.push(page);
+        }
+        if let Some(filter) = &self.filter {
+            let mut seen_titles = std::collections::HashSet::new();
+            pages.extend(pedia.pages.iter().filter(|page| {
+                (filter.is_match(&page.key)
+                    || filter.is_match(&page.title)
+                    || filter.is_match(&page.prefab_name))
+                    && !page.title.starts_with("<N:")
+                    && seen_titles.insert(page.title.as_str())
+            }));
+        }
+        Ok(pages)
+    }
+
+    /// Compare the generated templates of `page` with `current`, printing the diffs.
+    fn compare(
+        &self,
+        page: &Page,
+        current: Option<&str>,
+        pedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<Status>> {
+        let generated = [
+            ("Itembox", page.item(pedia, config, diagnostics)?),
+            ("Structurebox", page.structure(pedia, diagnostics)?),
+            ("Recipe", page.item_recipe(pedia, config, diagnostics)?),
+            (
+                "Data Parameters",
+                page.data_network_properties(pedia, enums, config, diagnostics)?,
+            ),
+        ];
+        if generated.iter().all(|(_, out)| out.is_none()) {
+            return Ok(Some(Status::NotChecked));
+        }
+        let Some(current) = current else {
+            return Ok(Some(Status::Missing));
+        };
+        let mut status = None;
+        for (name, out) in &generated {
+            let Some(new) = out
+                .as_deref()
+                .and_then(|out| wikitext::find(out, name).map(|(r, _)| &out[r]))
+            else {
+                continue;
+            };
+            let Some(old) = wikitext::find(current, name).map(|(r, _)| &current[r]) else {
+                status = Some(Status::Missing);
+                continue;
+            };
+            let diff = unified_diff(
+                old,
+                new,
+                &format!("wiki/{} ({name})", page.title),
+                &format!("generated/{} ({name})", page.title),
+            );
+            if diff.is_empty() {
+                continue;
+            }
+            if !self.quiet {
+                print!("{diff}");
+            }
+            let edited = match provenance_version(current) {
+                Some(version) if Some(&version) != pedia.version.as_ref() => Status::Stale,
+                _ => Status::HandEdited,
+            };
+            status = Some(status.map_or(edited, |s: Status| s.min(edited)));
+        }
+        Ok(status)
+    }
+
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        verbose: bool,
+    ) -> color_eyre::Result<()> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(self._run(stationpedia, enums, config, verbose))
+    }
+
+    async fn _run(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let pages = self.pages(stationpedia)?;
+        let source = match &self.recorded {
+            Some(dir) => Source::Recorded(dir.clone()),
+            None => Source::Wiki(Wiki::connect(config, self.api_url.as_deref(), false).await?),
+        };
+        if let Some(dir) = &self.record {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut diagnostics = Diagnostics::default();
+        let mut found = vec![];
+        for page in &pages {
+            if verbose {
+                eprintln!("checking {}", page.title);
+            }
+            let current = source.wikitext(&page.title).await?;
+            if let (Some(dir), Some(current)) = (&self.record, &current) {
+                std::fs::write(dir.join(file_name(&page.title)), current)?;
+            }
+            let status = self.compare(
+                page,
+                current.as_deref(),
+                stationpedia,
+                enums,
+                config,
+                &mut diagnostics,
+            )?;
+            if let Some(status) = status {
+                found.push((status, &page.title));
+            }
+        }
+        diagnostics.report();
+
+        found.sort();
+        for (status, title) in &found {
+            println!("{status:11} {title}");
+        }
+        let unchecked = found
+            .iter()
+            .filter(|(status, _)| *status == Status::NotChecked)
+            .count();
+        eprintln!(
+            "{} of {} pages up to date",
+            pages.len() - found.len(),
+            pages.len() - unchecked
+        );
+        let differ = found.len() - unchecked;
+        if differ > 0 {
+            eyre::bail!("{differ} pages differ from the generated output");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+//! Checks `config.toml` against the loaded dumps.
+//!
+//! Loading the config already rejects unknown keys and values of the wrong shape, this looks for names that don't
+//! exist in the game data, like a misspelled prefab or a logic type a device doesn't have.
+
+use color_eyre::eyre;
+
+use crate::{
+    config::{Config, Spanned},
+    enums::Enums,
+    stationpedia::Stationpedia,
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    sub: ConfigSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ConfigSubcommand {
+    /// Validate the config against the stationpedia and enums
+    Check,
+}
+
+/// A problem found in the config, at a location in the file.
+struct Problem {
+    span: std::ops::Range<usize>,
+    message: String,
+}
+
+#[derive(Default)]
+struct Problems(Vec<Problem>);
+
+impl Problems {
+    fn push(&mut self, key: &Spanned<String>, message: String) {
+        self.0.push(Problem {
+            span: key.span(),
+            message,
+        });
+    }
+}
+
+fn check(stationpedia: &Stationpedia, enums: &Enums, config: &Config) -> Problems {
+    let mut problems = Problems::default();
+    let has_enum = |listing: &str, name: &str| {
+        enums
+            .script_enums
+            .get(listing)
+            .is_some_and(|l| l.values.contains_key(name))
+    };
+
+    for name in config.logic.types.keys() {
+        if !has_enum("LogicType", name.get_ref()) {
+            problems.push(name, format!("unknown logic type `{}`", name.get_ref()));
+        }
+    }
+    for name in config.logic.slot_types.keys() {
+        if !has_enum("LogicSlotType", name.get_ref()) {
+            problems.push(
+                name,
+                format!("unknown logic slot type `{}`", name.get_ref()),
+            );
+        }
+    }
+    for (prefab_name, device) in &config.logic.device {
+        let Some(page) = stationpedia.lookup_prefab_name(prefab_name.get_ref()) else {
+            problems.push(
+                prefab_name,
+                format!("no page with prefab name `{}`", prefab_name.get_ref()),
+            );
+            continue;
+        };
+        for name in device.types.keys() {
+            if !has_enum("LogicType", name.get_ref()) {
+                problems.push(name, format!("unknown logic type `{}`", name.get_ref()));
+            } else if !page
+                .logic_info
+                .as_ref()
+                .is_some_and(|l| l.logic_types.types.contains_key(name.get_ref()))
+            {
+                problems.push(
+                    name,
+                    format!(
+                        "{} has no logic type `{}`",
+                        page.prefab_name,
+                        name.get_ref()
+                    ),
+                );
+            }
+        }
+        for name in device.slot_types.keys() {
+            if !has_enum("LogicSlotType", name.get_ref()) {
+                problems.push(
+                    name,
+                    format!("unknown logic slot type `{}`", name.get_ref()),
+                );
+            }
+        }
+    }
+
+    let device_replace = config.logic.device.values().flat_map(|d| &d.replace);
+    for replace in config.logic.replace.iter().chain(device_replace) {
+        if let Err(e) = regex::Regex::new(replace.regex.get_ref()) {
+            problems.push(&replace.regex, format!("invalid regex: {e}"));
+        }
+    }
+
+    for (path, category) in config.instructions.walk() {
+        for instruction in &category.instructions {
+            let op = &instruction.get_ref().op;
+            if !stationpedia.script_commands.contains_key(op) {
+                problems.0.push(Problem {
+                    span: instruction.span(),
+                    message: format!(
+                        "instruction `{op}` in `{}` is not in the stationpedia",
+                        path.join(".")
+                    ),
+                });
+            }
+        }
+    }
+
+    for name in config.enums.keys() {
+        let known = enums
+            .script_enums
+            .iter()
+            .chain(&enums.basic_enums)
+            .any(|(key, l)| key == name.get_ref() || &l.enum_name == name.get_ref());
+        if !known {
+            problems.push(name, format!("unknown enum `{}`", name.get_ref()));
+        }
+    }
+    problems.0.sort_by_key(|p| p.span.start);
+    problems
+}
+
+impl ConfigCommand {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        match self.sub {
+            ConfigSubcommand::Check => {
+                let problems = check(stationpedia, enums, config);
+                for problem in &problems.0 {
+                    eprintln!(
+                        "{}: {}",
+                        config.location(problem.span.clone()),
+                        problem.message
+                    );
+                }
+                if !problems.0.is_empty() {
+                    eyre::bail!("found {} problems in the config", problems.0.len());
+                }
+                eprintln!("config is valid");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//! Generates the reference of enum constants usable in IC10, like `LogicType.Temperature`.
+
+use std::{collections::BTreeMap, fmt::Write as _, path::PathBuf};
+
+use serde_derive::Serialize;
+
+use crate::{
+    enums::Enums,
+    stationpedia::{GameVersion, Stationpedia},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Wiki,
+    Json,
+    Toml,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct Constants {
+    /// Output format, `json` and `toml` are meant for editor autocompletion
+    #[arg(long, value_enum, default_value_t = Format::Wiki)]
+    format: Format,
+    /// Write to this file instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// All constants, grouped by the enum prefix used in IC10.
+#[derive(Debug, Serialize)]
+pub struct ConstantsExport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
+    pub enums: BTreeMap<String, BTreeMap<String, Constant>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Constant {
+    pub value: i64,
+    pub deprecated: bool,
+    pub description: String,
+}
+
+impl ConstantsExport {
+    pub fn new(stationpedia: &Stationpedia, enums: &Enums) -> Self {
+        let mut export = ConstantsExport {
+            version: stationpedia.version.clone(),
+            enums: BTreeMap::new(),
+        };
+        // IC10 uses the key as prefix, listings without a name (`_unnamed`) can't be referred to
+        for (prefix, listing) in enums.script_enums.iter().chain(&enums.basic_enums) {
+            if prefix.starts_with('_') || export.enums.contains_key(prefix) {
+                continue;
+            }
+            let constants = listing
+                .values
+                .iter()
+                .map(|(name, entry)| {
+                    let constant = Constant {
+                        value: entry.value,
+                        deprecated: entry.deprecated,
+                        description: entry.description.trim().to_owned(),
+                    };
+                    (name.clone(), constant)
+                })
+                .collect();
+            export.enums.insert(prefix.clone(), constants);
+        }
+        export
+    }
+
+    /// One section per enum with a table of its constants, deprecated ones in a table of their own.
+    pub fn wiki(&self, stationpedia: &Stationpedia) -> color_eyre::Result<String> {
+        let mut out = super::provenance(stationpedia);
+        let table = |out: &mut String, constants: &[(&String, &Constant)], prefix: &str| {
+            out.push_str("{| class=\"wikitable sortable\"\n! Constant !! Value\n");
+            for (name, constant) in constants {
+                writeln!(
+                    out,
+                    "|-\n| <code>{prefix}.{name}</code> || {}",
+                    constant.value
+                )?;
+            }
+            out.push_str("|}\n");
+            color_eyre::Result::<()>::Ok(())
+        };
+        for (prefix, constants) in &self.enums {
+            let mut constants = constants.iter().collect::<Vec<_>>();
+            constants.sort_by_key(|(name, c)| (c.value, name.as_str()));
+            let (deprecated, current) = constants
+                .into_iter()
+                .partition::<Vec<_>, _>(|(_, c)| c.deprecated);
+            writeln!(out, "\n== {prefix} ==")?;
+            table(&mut out, &current, prefix)?;
+            if !deprecated.is_empty() {
+                writeln!(out, "=== Deprecated ===")?;
+                table(&mut out, &deprecated, prefix)?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl Constants {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let export = ConstantsExport::new(stationpedia, enums);
+        let out = match self.format {
+            Format::Wiki => export.wiki(stationpedia)?,
+            Format::Json => serde_json::to_string_pretty(&export)? + "\n",
+            Format::Toml => toml_edit::ser::to_string_pretty(&export)?,
+        };
+        match &self.out {
+            Some(path) => std::fs::write(path, out)?,
+            None => print!("{out}"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Total raw materials needed to make an item or build a structure.
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use color_eyre::eyre;
+
+use crate::{
+    diagnostics::{Diagnostics, Missing, Section},
+    enums::Enums,
+    stationpedia::{MachineTier, Page, Reagent, Recipe, Stationpedia},
+    wikitext::{Layout, Template},
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct Cost {
+    /// Key, title, prefab name or prefab hash of the page
+    item: String,
+    /// Prefer recipes of this machine tier, e.g. `TierTwo` or `2`
+    #[arg(long)]
+    tier: Option<MachineTier>,
+    /// Prefer recipes made by this creator prefab, can be given multiple times in order of preference
+    #[arg(long)]
+    creator: Vec<String>,
+    /// Output a `{{Cost}}` template instead of a table
+    #[arg(long)]
+    template: bool,
+}
+
+/// Raw reagents and items without a recipe, with their total amounts.
+#[derive(Debug, Default)]
+pub struct Bill {
+    pub reagents: BTreeMap<String, f64>,
+    /// Prefab names of consumed items that can't be expanded further
+    pub items: BTreeMap<String, f64>,
+}
+
+struct Expander<'a> {
+    pedia: &'a Stationpedia,
+    tier: Option<&'a MachineTier>,
+    creators: &'a [String],
+    /// Key of the page the bill is for, for diagnostics
+    page: &'a str,
+    diagnostics: &'a mut Diagnostics,
+    /// Prefabs currently being expanded, to break cycles
+    stack: Vec<String>,
+}
+
+impl<'a> Expander<'a> {
+    /// The recipe to use for a page, following the preferred creators and tier.
+    fn recipe<'p>(&self, page: &'p Page) -> Option<&'p Recipe> {
+        let recipes = &page.item.as_ref()?.recipes;
+        self.creators
+            .iter()
+            .find_map(|c| recipes.iter().find(|r| &r.creator_prefab_name == c))
+            .or_else(|| {
+                let tier = self.tier?;
+                recipes.iter().find(|r| r.tier_name == tier.as_str())
+            })
+            .or(recipes.first())
+    }
+
+    /// Kits that construct the structure on `page`.
+    fn kits(&self, page: &Page) -> Vec<&'a Page> {
+        self.pedia
+            .pages
+            .iter()
+            .filter(|p| {
+                p.constructs
+                    .iter()
+                    .any(|c| c.prefab_hash == page.prefab_hash)
+            })
+            .collect()
+    }
+
+    fn expand(&mut self, bill: &mut Bill, prefab_name: &str, quantity: f64) {
+        let Some(page) = self.pedia.lookup_prefab_name(prefab_name) else {
+            self.diagnostics.push(
+                self.page,
+                Section::Recipe,
+                Missing::PrefabName(prefab_name.to_owned()),
+            );
+            *bill.items.entry(prefab_name.to_owned()).or_default() += quantity;
+            return;
+        };
+        if self.stack.iter().any(|p| p == prefab_name) {
+            self.diagnostics.push(
+                self.page,
+                Section::Recipe,
+                Missing::Unexpected(format!("recipe cycle through `{prefab_name}`")),
+            );
+            *bill.items.entry(prefab_name.to_owned()).or_default() += quantity;
+            return;
+        }
+        self.stack.push(prefab_name.to_owned());
+        if let Some(recipe) = self.recipe(page) {
+            for (reagent, amount) in recipe.reagents.iter().filter(|(_, q)| *q > &0.0) {
+                *bill.reagents.entry(reagent.clone()).or_default() += amount * quantity;
+            }
+        } else if let Some(structure) = &page.structure {
+            let consumed = structure
+                .build_states
+                .0
+                .iter()
+                .flat_map(|s| s.tool.iter().flatten())
+                .filter(|t| !t.is_tool)
+                .collect::<Vec<_>>();
+            // the kit is usually consumed by the first build state, only add it if it isn't
+            let kits = self.kits(page);
+            if let Some(kit) = kits.first() {
+                if !consumed
+                    .iter()
+                    .any(|t| kits.iter().any(|k| k.prefab_name == t.prefab_name))
+                {
+                    self.expand(bill, &kit.prefab_name, quantity);
+                }
+            }
+            for tool in consumed {
+                let n = tool.quantity.unwrap_or(1) as f64;
+                self.expand(bill, &tool.prefab_name, n * quantity);
+            }
+        } else {
+            *bill.items.entry(prefab_name.to_owned()).or_default() += quantity;
+        }
+        self.stack.pop();
+    }
+}
+
+impl Bill {
+    /// Expand `page` into raw reagents.
+    pub fn new(
+        pedia: &Stationpedia,
+        page: &Page,
+        tier: Option<&MachineTier>,
+        creators: &[String],
+        diagnostics: &mut Diagnostics,
+    ) -> Self {
+        let mut bill = Bill::default();
+        let mut expander = Expander {
+            pedia,
+            tier,
+            creators,
+            page: &page.key,
+            diagnostics,
+            stack: vec![],
+        };
+        expander.expand(&mut bill, &page.prefab_name, 1.0);
+        bill
+    }
+}
+
+/// Unit of a reagent from `Reagent.Unit`, see [`Reagent::unit_suffix`].
+fn unit<'a>(pedia: &'a Stationpedia, reagent: &str) -> &'a str {
+    pedia
+        .reagents
+        .get(reagent)
+        .map_or(" x", Reagent::unit_suffix)
+}
+
+/// Title of the page with `prefab_name`, or the prefab name if there is none.
+fn title<'a>(pedia: &'a Stationpedia, prefab_name: &'a str) -> &'a str {
+    pedia
+        .lookup_prefab_name(prefab_name)
+        .map_or(prefab_name, |p| p.title.as_str())
+}
+
+impl Cost {
+    fn find_page<'a>(&self, pedia: &'a Stationpedia) -> color_eyre::Result<&'a Page> {
+        let item = self.item.as_str();
+        item.parse::<i64>()
+            .ok()
+            .and_then(|hash| pedia.lookup_hash(hash))
+            .or_else(|| pedia.lookup_prefab_name(item))
+            .or_else(|| pedia.lookup_key(item))
+            .or_else(|| pedia.lookup_title_ignore_case(item))
+            .ok_or_else(|| eyre::eyre!("no page found for `{item}`"))
+    }
+
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        _enums: &Enums,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let page = self.find_page(stationpedia)?;
+        let mut diagnostics = Diagnostics::default();
+        let bill = Bill::new(
+            stationpedia,
+            page,
+            self.tier.as_ref(),
+            &self.creator,
+            &mut diagnostics,
+        );
+
+        let mut out = String::new();
+        if self.template {
+            out.push_str(&super::provenance(stationpedia));
+            let mut template = Template::new("Cost");
+            for (reagent, amount) in &bill.reagents {
+                template.param(reagent, format!("{amount}{}", unit(stationpedia, reagent)));
+            }
+            for (prefab_name, amount) in &bill.items {
+                template.param(title(stationpedia, prefab_name), format!("{amount} x"));
+            }
+            writeln!(out, "{}", template.render(Layout::Pretty))?;
+        } else {
+            writeln!(out, "Cost of {} ({})", page.title, page.prefab_name)?;
+            let width = bill
+                .reagents
+                .keys()
+                .map(|r| r.len())
+                .chain(bill.items.keys().map(|p| title(stationpedia, p).len()))
+                .max()
+                .unwrap_or_default();
+            for (reagent, amount) in &bill.reagents {
+                writeln!(
+                    out,
+                    "  {reagent:width$}  {amount}{}",
+                    unit(stationpedia, reagent)
+                )?;
+            }
+            if !bill.items.is_empty() {
+                writeln!(out, "Items without a recipe:")?;
+                for (prefab_name, amount) in &bill.items {
+                    writeln!(
+                        out,
+                        "  {:width$}  {amount} x",
+                        title(stationpedia, prefab_name)
+                    )?;
+                }
+            }
+        }
+        print!("{out}");
+        diagnostics.report();
+        Ok(())
+    }
+}
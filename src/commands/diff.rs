@@ -0,0 +1,346 @@
+//! Compare two stationpedia dumps, e.g. before and after a game update.
+
+use std::{collections::BTreeMap, fmt::Write as _, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    enums::{EnumEntry, EnumListing, Enums},
+    stationpedia::{GameVersion, Page, Recipe, Stationpedia},
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct Diff {
+    /// The old `Stationpedia.json`, compared against `--stationpedia`
+    old_stationpedia: PathBuf,
+    /// The old `Enums.json`, compared against `--enums`
+    old_enums: PathBuf,
+    /// Also write the diff as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+/// All differences between two stationpedia and enums dumps.
+#[derive(Debug, serde_derive::Serialize)]
+pub struct PediaDiff {
+    pub old_version: Option<GameVersion>,
+    pub new_version: Option<GameVersion>,
+    /// Prefab names of pages only in the new dump
+    pub added: Vec<String>,
+    /// Prefab names of pages only in the old dump
+    pub removed: Vec<String>,
+    /// Changed fields per prefab name
+    pub changed: BTreeMap<String, Vec<FieldChange>>,
+    pub script_commands: Vec<FieldChange>,
+    pub enums: Vec<FieldChange>,
+}
+
+/// A changed value, `old` is `null` if it was added and `new` is `null` if it was removed.
+#[derive(Debug, serde_derive::Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let FieldChange { field, old, new } = self;
+        match (old, new) {
+            (serde_json::Value::Null, new) => write!(f, "+ {field}: {new}"),
+            (old, serde_json::Value::Null) => write!(f, "- {field}: {old}"),
+            (old, new) => write!(f, "~ {field}: {old} -> {new}"),
+        }
+    }
+}
+
+fn diff_value<T: Serialize + PartialEq>(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    old: &T,
+    new: &T,
+) -> color_eyre::Result<()> {
+    if old != new {
+        diff_json(
+            changes,
+            field.to_owned(),
+            serde_json::to_value(old)?,
+            serde_json::to_value(new)?,
+        );
+    }
+    Ok(())
+}
+
+/// Diff two json values, descending into objects so only the changed keys are reported.
+fn diff_json(
+    changes: &mut Vec<FieldChange>,
+    field: String,
+    old: serde_json::Value,
+    new: serde_json::Value,
+) {
+    use serde_json::Value;
+
+    match (old, new) {
+        (Value::Object(mut old), Value::Object(mut new)) => {
+            let keys = old
+                .keys()
+                .chain(new.keys())
+                .cloned()
+                .collect::<std::collections::BTreeSet<_>>();
+            for key in keys {
+                let old = old.remove(&key).unwrap_or(Value::Null);
+                let new = new.remove(&key).unwrap_or(Value::Null);
+                if old != new {
+                    diff_json(changes, format!("{field}.{key}"), old, new);
+                }
+            }
+        }
+        (old, new) => changes.push(FieldChange { field, old, new }),
+    }
+}
+
+/// Diff two keyed collections, reporting each key as `{field}.{key}`.
+fn diff_map<'a, V: Serialize + PartialEq + 'a>(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    old: impl IntoIterator<Item = (String, &'a V)>,
+    new: impl IntoIterator<Item = (String, &'a V)>,
+) -> color_eyre::Result<()> {
+    let old = old.into_iter().collect::<BTreeMap<_, _>>();
+    let new = new.into_iter().collect::<BTreeMap<_, _>>();
+    let prefix = |key: &str| {
+        if field.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{field}.{key}")
+        }
+    };
+    for (key, old_value) in &old {
+        match new.get(key) {
+            Some(new_value) => diff_value(changes, &prefix(key), old_value, new_value)?,
+            None => changes.push(FieldChange {
+                field: prefix(key),
+                old: serde_json::to_value(old_value)?,
+                new: serde_json::Value::Null,
+            }),
+        }
+    }
+    for (key, new_value) in new.iter().filter(|(k, _)| !old.contains_key(*k)) {
+        changes.push(FieldChange {
+            field: prefix(key),
+            old: serde_json::Value::Null,
+            new: serde_json::to_value(new_value)?,
+        });
+    }
+    Ok(())
+}
+
+fn diff_page(old: &Page, new: &Page) -> color_eyre::Result<Vec<FieldChange>> {
+    let mut changes = vec![];
+    diff_value(&mut changes, "Title", &old.title, &new.title)?;
+    diff_value(
+        &mut changes,
+        "Description",
+        &old.description,
+        &new.description,
+    )?;
+    diff_value(
+        &mut changes,
+        "MaxQuantity",
+        &old.item.as_ref().and_then(|i| i.max_quantity),
+        &new.item.as_ref().and_then(|i| i.max_quantity),
+    )?;
+    diff_value(
+        &mut changes,
+        "BuildStates",
+        &old.structure.as_ref().map(|s| &s.build_states),
+        &new.structure.as_ref().map(|s| &s.build_states),
+    )?;
+    diff_map(&mut changes, "Recipes", recipes(old), recipes(new))?;
+    diff_map(
+        &mut changes,
+        "LogicTypes",
+        logic_types(old),
+        logic_types(new),
+    )?;
+    Ok(changes)
+}
+
+/// Recipes keyed by creator, some creators have multiple recipes for the same item.
+fn recipes(page: &Page) -> Vec<(String, &Recipe)> {
+    let mut seen = BTreeMap::<&str, usize>::new();
+    page.item
+        .iter()
+        .flat_map(|i| &i.recipes)
+        .map(|r| {
+            let n = seen.entry(&r.creator_prefab_name).or_default();
+            *n += 1;
+            let key = if *n == 1 {
+                r.creator_prefab_name.clone()
+            } else {
+                format!("{}#{n}", r.creator_prefab_name)
+            };
+            (key, r)
+        })
+        .collect()
+}
+
+fn logic_types(page: &Page) -> Vec<(String, &String)> {
+    page.logic_info
+        .iter()
+        .flat_map(|l| &l.logic_types.types)
+        .map(|(k, v)| (k.clone(), v))
+        .collect()
+}
+
+/// Enum entries keyed by `{listing}.{name}`.
+fn enum_entries(enums: &BTreeMap<String, EnumListing>) -> Vec<(String, &EnumEntry)> {
+    enums
+        .iter()
+        .flat_map(|(listing, l)| {
+            l.values
+                .iter()
+                .map(move |(name, entry)| (format!("{listing}.{name}"), entry))
+        })
+        .collect()
+}
+
+fn diff_enums(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    old: &BTreeMap<String, EnumListing>,
+    new: &BTreeMap<String, EnumListing>,
+) -> color_eyre::Result<()> {
+    diff_map(changes, field, enum_entries(old), enum_entries(new))
+}
+
+impl PediaDiff {
+    pub fn new(
+        old: &Stationpedia,
+        old_enums: &Enums,
+        new: &Stationpedia,
+        new_enums: &Enums,
+    ) -> color_eyre::Result<Self> {
+        let mut diff = PediaDiff {
+            old_version: old.version.clone(),
+            new_version: new.version.clone(),
+            added: vec![],
+            removed: vec![],
+            changed: BTreeMap::new(),
+            script_commands: vec![],
+            enums: vec![],
+        };
+        for old_page in &old.pages {
+            let Some(new_page) = new.lookup_prefab_name(&old_page.prefab_name) else {
+                diff.removed.push(old_page.prefab_name.clone());
+                continue;
+            };
+            let changes = diff_page(old_page, new_page)?;
+            if !changes.is_empty() {
+                diff.changed.insert(old_page.prefab_name.clone(), changes);
+            }
+        }
+        for new_page in &new.pages {
+            if old.lookup_prefab_name(&new_page.prefab_name).is_none() {
+                diff.added.push(new_page.prefab_name.clone());
+            }
+        }
+        diff_map(
+            &mut diff.script_commands,
+            "",
+            old.script_commands.iter().map(|(k, v)| (k.clone(), v)),
+            new.script_commands.iter().map(|(k, v)| (k.clone(), v)),
+        )?;
+        diff_enums(
+            &mut diff.enums,
+            "scriptEnums",
+            &old_enums.script_enums,
+            &new_enums.script_enums,
+        )?;
+        diff_enums(
+            &mut diff.enums,
+            "basicEnums",
+            &old_enums.basic_enums,
+            &new_enums.basic_enums,
+        )?;
+        Ok(diff)
+    }
+
+    /// Human readable report of the diff.
+    pub fn report(&self, old: &Stationpedia, new: &Stationpedia) -> color_eyre::Result<String> {
+        let mut out = String::new();
+        let title = |pedia: &Stationpedia, prefab_name: &str| {
+            pedia
+                .lookup_prefab_name(prefab_name)
+                .map(|p| p.title.clone())
+                .unwrap_or_default()
+        };
+        let version = |version: &Option<GameVersion>| {
+            version
+                .as_ref()
+                .map_or_else(|| "(no version)".to_owned(), ToString::to_string)
+        };
+        writeln!(
+            out,
+            "Stationpedia {} -> {}",
+            version(&self.old_version),
+            version(&self.new_version)
+        )?;
+        if !self.added.is_empty() {
+            writeln!(out, "\nAdded pages ({}):", self.added.len())?;
+            for prefab_name in &self.added {
+                writeln!(out, "  + {prefab_name} ({})", title(new, prefab_name))?;
+            }
+        }
+        if !self.removed.is_empty() {
+            writeln!(out, "\nRemoved pages ({}):", self.removed.len())?;
+            for prefab_name in &self.removed {
+                writeln!(out, "  - {prefab_name} ({})", title(old, prefab_name))?;
+            }
+        }
+        if !self.changed.is_empty() {
+            writeln!(out, "\nChanged pages ({}):", self.changed.len())?;
+            for (prefab_name, changes) in &self.changed {
+                writeln!(out, "  {prefab_name} ({})", title(new, prefab_name))?;
+                for change in changes {
+                    writeln!(out, "    {change}")?;
+                }
+            }
+        }
+        if !self.script_commands.is_empty() {
+            writeln!(
+                out,
+                "\nChanged script commands ({}):",
+                self.script_commands.len()
+            )?;
+            for change in &self.script_commands {
+                writeln!(out, "  {change}")?;
+            }
+        }
+        if !self.enums.is_empty() {
+            writeln!(out, "\nChanged enums ({}):", self.enums.len())?;
+            for change in &self.enums {
+                writeln!(out, "  {change}")?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl Diff {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let old: Stationpedia = crate::load_json(&self.old_stationpedia)?;
+        let old_enums: Enums = crate::load_json(&self.old_enums)?;
+        let diff = PediaDiff::new(&old, &old_enums, stationpedia, enums)?;
+        if let Some(path) = &self.json {
+            std::fs::write(path, serde_json::to_string_pretty(&diff)?)?;
+        }
+        print!("{}", diff.report(&old, stationpedia)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! Generates reference tables for the enums in `Enums.json`.
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use color_eyre::eyre;
+
+use crate::{
+    config::Config,
+    enums::{EnumListing, Enums},
+    stationpedia::Stationpedia,
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct EnumsPage {
+    /// Name of the enum listing, e.g. `LogicType` or `SortingClass`
+    #[arg(required_unless_present = "all")]
+    listing: Option<String>,
+    /// Generate every listing, writing them to `<out-dir>/<Name>.wiki`
+    #[arg(long, conflicts_with = "listing", requires = "out_dir")]
+    all: bool,
+    /// Directory to write pages to when using `--all`
+    #[arg(long, requires = "all")]
+    out_dir: Option<PathBuf>,
+}
+
+/// All listings with their key in `Enums.json`, script enums first.
+/// Basic enums that are the same as a script enum are left out.
+fn listings(enums: &Enums) -> Vec<(&str, &EnumListing)> {
+    let mut listings = enums
+        .script_enums
+        .iter()
+        .map(|(k, l)| (k.as_str(), l))
+        .collect::<Vec<_>>();
+    for (key, listing) in &enums.basic_enums {
+        if !listings.iter().any(|(_, l)| *l == listing) {
+            listings.push((key, listing));
+        }
+    }
+    listings
+}
+
+/// A wiki table for an enum listing, preceded by the intro text from `enums.<Name>.intro` in the config.
+fn enum_page(
+    pedia: &Stationpedia,
+    config: &Config,
+    listing: &EnumListing,
+) -> color_eyre::Result<String> {
+    let mut out = String::new();
+    if let Some(intro) = config
+        .enums
+        .get(listing.enum_name.as_str())
+        .and_then(|c| c.intro.as_deref())
+    {
+        writeln!(out, "{}\n", textwrap::dedent(intro).trim())?;
+    }
+    let mut values = listing.values.iter().collect::<Vec<_>>();
+    values.sort_by_key(|(name, entry)| (entry.value, name.as_str()));
+    let has_deprecated = values.iter().any(|(_, e)| e.deprecated);
+
+    out.push_str("{| class=\"wikitable sortable\"\n");
+    out.push_str("! Name !! Value !! Description");
+    if has_deprecated {
+        out.push_str(" !! Deprecated");
+    }
+    for (name, entry) in values {
+        let description = super::wikibox::wikify(&entry.description, pedia, config)?;
+        write!(out, "\n|-\n| {name} || {} || {description}", entry.value)?;
+        if has_deprecated {
+            out.push_str(if entry.deprecated { " || Yes" } else { " ||" });
+        }
+    }
+    out.push_str("\n|}\n");
+    Ok(out)
+}
+
+impl EnumsPage {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let listings = listings(enums);
+        if self.all {
+            let out_dir = self
+                .out_dir
+                .as_deref()
+                .ok_or_else(|| eyre::eyre!("--all requires --out-dir"))?;
+            std::fs::create_dir_all(out_dir)?;
+            for (_, listing) in &listings {
+                let page = enum_page(stationpedia, config, listing)?;
+                std::fs::write(
+                    out_dir.join(format!("{}.wiki", listing.enum_name)),
+                    super::provenance(stationpedia) + &page,
+                )?;
+            }
+            eprintln!("Wrote {} enums to {}", listings.len(), out_dir.display());
+        } else {
+            let name = self
+                .listing
+                .as_deref()
+                .ok_or_else(|| eyre::eyre!("no enum listing given"))?;
+            // the key and the name differ for some, e.g. `SlotClass` is named `Class`
+            let Some((_, listing)) = listings.iter().find(|(key, l)| {
+                key.eq_ignore_ascii_case(name) || l.enum_name.eq_ignore_ascii_case(name)
+            }) else {
+                let names = listings
+                    .iter()
+                    .map(|(_, l)| l.enum_name.as_str())
+                    .collect::<Vec<_>>();
+                eyre::bail!(
+                    "no enum named `{name}`, expected one of {}",
+                    names.join(", ")
+                );
+            };
+            print!("{}", super::provenance(stationpedia));
+            print!("{}", enum_page(stationpedia, config, listing)?);
+        }
+        Ok(())
+    }
+}
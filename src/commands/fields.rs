@@ -0,0 +1,76 @@
+//! Read template fields back from a page, or change them in place
+
+use std::path::PathBuf;
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{enums::Enums, merge::INFOBOXES, stationpedia::Stationpedia, wikitext};
+
+#[derive(Debug, clap::Parser)]
+pub struct Fields {
+    /// Page to read, e.g. one saved by `check --record`
+    file: PathBuf,
+    /// Only print the value of this field
+    field: Option<String>,
+    /// Template to read, defaults to the `Itembox` or `Structurebox` on the page
+    #[arg(long)]
+    template: Option<String>,
+    /// Set a field as `FIELD=VALUE` and write the page back, keeping the rest of its text as it was
+    #[arg(long = "set", value_parser = parse_field, conflicts_with = "field")]
+    set: Vec<(String, String)>,
+}
+
+fn parse_field(s: &str) -> color_eyre::Result<(String, String)> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("expected `FIELD=VALUE`, got `{s}`"))?;
+    Ok((name.trim().to_owned(), value.trim().to_owned()))
+}
+
+impl Fields {
+    pub(crate) fn run(
+        &self,
+        _stationpedia: &Stationpedia,
+        _enums: &Enums,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let text = std::fs::read_to_string(&self.file)
+            .wrap_err_with(|| format!("couldn't read {}", self.file.display()))?;
+        let names = match &self.template {
+            Some(name) => vec![name.as_str()],
+            None => INFOBOXES.to_vec(),
+        };
+        let Some((range, mut template)) = names.iter().find_map(|name| wikitext::find(&text, name))
+        else {
+            eyre::bail!("no {} in {}", names.join(" or "), self.file.display());
+        };
+
+        if !self.set.is_empty() {
+            for (field, value) in &self.set {
+                template.set(field, value);
+            }
+            let mut text = text;
+            text.replace_range(range, &template.to_string());
+            std::fs::write(&self.file, text)?;
+            return Ok(());
+        }
+
+        if let Some(field) = &self.field {
+            let value = template
+                .get(field)
+                .ok_or_else(|| eyre::eyre!("{} has no field `{field}`", template.name()))?;
+            println!("{}", wikitext::unescape(value));
+            return Ok(());
+        }
+
+        let params = template.params().collect::<Vec<_>>();
+        let width = params.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        println!("{}", template.name());
+        for (name, value) in &params {
+            // multi-line values, like the rows of `{{Data Parameters|`, line up under the first line
+            let value = wikitext::unescape(value).replace('\n', &format!("\n  {:width$}   ", ""));
+            println!("  {name:width$} = {value}");
+        }
+        Ok(())
+    }
+}
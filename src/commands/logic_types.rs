@@ -0,0 +1,58 @@
+//! Lists the type, unit and range of every `LogicType`, and which ones are still guessed from their name.
+
+use crate::{
+    config::Config,
+    enums::{Enums, LogicTypes},
+    stationpedia::Stationpedia,
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct LogicTypesReport {
+    /// Only list logic types that fall back to guessing from the name
+    #[arg(long)]
+    fallbacks: bool,
+}
+
+impl LogicTypesReport {
+    pub(crate) fn run(
+        &self,
+        _stationpedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let types = LogicTypes::new(enums, config)?;
+        if self.fallbacks {
+            for name in types.fallbacks() {
+                println!("{name}");
+            }
+            return Ok(());
+        }
+        let width = types.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+        for (name, info) in types.iter() {
+            let values = info
+                .values
+                .iter()
+                .map(|(v, n)| format!("{v}={n}"))
+                .collect::<Vec<_>>();
+            let line = format!(
+                "{name:width$}  {:7}  {:5}  {:30}  {:10}  {}",
+                info.ty.as_str(),
+                info.unit.as_deref().unwrap_or("-"),
+                info.range.as_deref().unwrap_or("-"),
+                info.source.to_string(),
+                values.join(", ")
+            );
+            println!("{}", line.trim_end());
+        }
+        let fallbacks = types.fallbacks().collect::<Vec<_>>();
+        if !fallbacks.is_empty() {
+            eprintln!(
+                "{} logic types are guessed from their name: {}",
+                fallbacks.len(),
+                fallbacks.join(", ")
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! Pack and unpack the 64-bit instruction words written to device memory with `put` and read with `get`.
+
+use color_eyre::eyre;
+
+use crate::{
+    enums::Enums,
+    stationpedia::{Instruction, Page, Stationpedia},
+};
+
+/// IC10 registers are doubles, so only integers up to this size are exact.
+const MAX_EXACT: i64 = 1 << 53;
+
+#[derive(Debug, clap::Parser)]
+pub struct Encode {
+    /// Prefab name or title of the device
+    device: String,
+    /// Name of the instruction, e.g. `ExecuteRecipe`
+    instruction: String,
+    /// Field values as `FIELD=VALUE`, e.g. `QUANTITY=5`. Hash fields also take a prefab or reagent name
+    #[arg(value_parser = parse_field)]
+    fields: Vec<(String, String)>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct Decode {
+    /// Prefab name or title of the device
+    device: String,
+    /// The instruction word, as read with `get`
+    #[arg(allow_negative_numbers = true)]
+    word: String,
+}
+
+fn parse_field(s: &str) -> color_eyre::Result<(String, String)> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("expected `FIELD=VALUE`, got `{s}`"))?;
+    Ok((name.trim().to_owned(), value.trim().to_owned()))
+}
+
+/// The device page, with the instructions it understands.
+fn find_device<'a>(
+    pedia: &'a Stationpedia,
+    device: &str,
+) -> color_eyre::Result<(&'a Page, Vec<(&'a String, &'a Instruction)>)> {
+    let page = pedia
+        .lookup_prefab_name(device)
+        .or_else(|| pedia.lookup_title_ignore_case(device))
+        .ok_or_else(|| eyre::eyre!("no page found for `{device}`"))?;
+    let instructions = page
+        .memory
+        .as_ref()
+        .and_then(|m| m.instructions.as_ref())
+        .map(|i| i.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    if instructions.is_empty() {
+        eyre::bail!("{} has no memory instructions", page.prefab_name);
+    }
+    Ok((page, instructions))
+}
+
+/// Value of a field, hash fields can be given as a prefab or reagent name.
+fn field_value(pedia: &Stationpedia, name: &str, value: &str) -> color_eyre::Result<i64> {
+    if let Ok(value) = value.parse::<i64>() {
+        return Ok(value);
+    }
+    if name.to_uppercase().ends_with("HASH") {
+        if let Some(page) = pedia.lookup_prefab_name(value) {
+            return Ok(page.prefab_hash);
+        }
+        if let Some(reagent) = pedia.reagents.get(value) {
+            return Ok(reagent.hash);
+        }
+    }
+    eyre::bail!("invalid value for `{name}`: `{value}`")
+}
+
+impl Encode {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        _enums: &Enums,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let (page, instructions) = find_device(stationpedia, &self.device)?;
+        let Some((_, instruction)) = instructions
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.instruction))
+        else {
+            let names = instructions
+                .iter()
+                .map(|(n, _)| n.as_str())
+                .collect::<Vec<_>>();
+            eyre::bail!(
+                "{} has no instruction `{}`, expected one of {}",
+                page.prefab_name,
+                self.instruction,
+                names.join(", ")
+            );
+        };
+        let values = self
+            .fields
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), field_value(stationpedia, name, value)?)))
+            .collect::<color_eyre::Result<Vec<_>>>()?;
+        let word = instruction.layout()?.encode(instruction.value, &values)? as i64;
+        if !(-MAX_EXACT..=MAX_EXACT).contains(&word) {
+            tracing::warn!("{word} can't be represented exactly in an IC10 register");
+        }
+        println!("{word}");
+        Ok(())
+    }
+}
+
+impl Decode {
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        _enums: &Enums,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let (page, instructions) = find_device(stationpedia, &self.device)?;
+        // registers are doubles, so accept `1.5E+15` style numbers too
+        let word = match self.word.parse::<i64>() {
+            Ok(word) => word,
+            Err(_) => {
+                let f = self
+                    .word
+                    .parse::<f64>()
+                    .map_err(|_| eyre::eyre!("invalid instruction word `{}`", self.word))?;
+                if f.fract() != 0.0 {
+                    eyre::bail!("instruction word `{}` is not an integer", self.word);
+                }
+                f as i64
+            }
+        } as u64;
+        for (name, instruction) in &instructions {
+            let layout = instruction.layout()?;
+            if layout.opcode(word) != Some(instruction.value) {
+                continue;
+            }
+            println!("{name} ({})", instruction.type_);
+            let fields = layout.decode(word);
+            let width = fields.iter().map(|(f, _)| f.name.len()).max().unwrap_or(0);
+            for (field, value) in fields {
+                let bits = format!("{}-{}", field.start, field.end);
+                print!("  {:width$}  {bits:5}  {value}", field.name);
+                if field.name.ends_with("HASH") {
+                    if let Some(page) = stationpedia.lookup_hash(value) {
+                        print!(" ({})", page.prefab_name);
+                    } else if let Some((reagent, _)) =
+                        stationpedia.reagents.iter().find(|(_, r)| r.hash == value)
+                    {
+                        print!(" ({reagent})");
+                    }
+                }
+                println!();
+            }
+            return Ok(());
+        }
+        eyre::bail!(
+            "no instruction of {} matches the opcode in `{}`",
+            page.prefab_name,
+            self.word
+        )
+    }
+}
@@ -0,0 +1,99 @@
+//! Merge generated pages into local copies of the existing pages
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{enums::Enums, stationpedia::Stationpedia};
+
+#[derive(Debug, clap::Parser)]
+pub struct Merge {
+    /// Generated `.wiki` file, or a directory of them like `wikibox --all` writes
+    generated: PathBuf,
+    /// The existing page, or a directory with the existing pages under the same file names
+    existing: PathBuf,
+    /// Write the merged pages here instead of stdout, a directory when merging directories. Can be the existing pages
+    /// to update them in place
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+fn read(path: &Path) -> color_eyre::Result<String> {
+    std::fs::read_to_string(path).wrap_err_with(|| format!("couldn't read {}", path.display()))
+}
+
+impl Merge {
+    pub(crate) fn run(
+        &self,
+        _stationpedia: &Stationpedia,
+        _enums: &Enums,
+        verbose: bool,
+    ) -> color_eyre::Result<()> {
+        if !self.generated.is_dir() {
+            let merged = crate::merge::merge(&read(&self.existing)?, &read(&self.generated)?)?;
+            merged.warn(&self.existing.display().to_string());
+            match &self.out {
+                Some(path) => std::fs::write(path, merged.text)?,
+                None => print!("{}", merged.text),
+            }
+            return Ok(());
+        }
+
+        if !self.existing.is_dir() {
+            eyre::bail!(
+                "{} is a directory, so {} has to be one too",
+                self.generated.display(),
+                self.existing.display()
+            );
+        }
+        let out_dir = self
+            .out
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("merging directories requires --out"))?;
+        std::fs::create_dir_all(out_dir)?;
+        let mut files = std::fs::read_dir(&self.generated)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        files.retain(|p| p.extension().is_some_and(|e| e == "wiki"));
+        files.sort();
+
+        let mut merged_count = 0;
+        let mut new = vec![];
+        for generated in &files {
+            let Some(file_name) = generated.file_name() else {
+                continue;
+            };
+            let existing = self.existing.join(file_name);
+            let text = if existing.exists() {
+                let merged = crate::merge::merge(&read(&existing)?, &read(generated)?)
+                    .wrap_err_with(|| format!("couldn't merge into {}", existing.display()))?;
+                merged.warn(&existing.display().to_string());
+                if verbose {
+                    eprintln!(
+                        "{}: replaced {}",
+                        existing.display(),
+                        merged.replaced.join(", ")
+                    );
+                }
+                merged_count += 1;
+                merged.text
+            } else {
+                new.push(generated);
+                read(generated)?
+            };
+            std::fs::write(out_dir.join(file_name), text)?;
+        }
+
+        eprintln!(
+            "Merged {merged_count} pages and copied {} new ones to {}",
+            new.len(),
+            out_dir.display()
+        );
+        if verbose {
+            for path in &new {
+                eprintln!("  new: {}", path.display());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "mw")]
+//! Publish generated pages to the wiki
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{
+    config::Config,
+    merge,
+    stationpedia::Stationpedia,
+    wiki::{Mode, Outcome, Wiki},
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct Publish {
+    /// `.wiki` files, or directories of them like `wikibox --all` writes. The file name is the page title, with `%2F`
+    /// for `/`
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    /// Title of the page to edit, instead of the file name. Only for a single file
+    #[arg(long)]
+    title: Option<String>,
+    /// What to do with pages that do or don't exist yet
+    #[arg(long, value_enum)]
+    mode: Mode,
+    /// Edit summary, defaults to `[bot] summary` in the config
+    #[arg(long)]
+    summary: Option<String>,
+    /// API endpoint to use instead of `[bot] api_url`, e.g. a local test wiki
+    #[arg(long)]
+    api_url: Option<String>,
+    /// Only replace the generated regions of existing pages, keeping the rest of them, see `merge`
+    #[arg(long)]
+    merge: bool,
+    /// Show what would happen to each page, without logging in or editing
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Page title of a generated file, undoing the `%2F` escaping of `wikibox --all`.
+fn title_of(path: &Path) -> color_eyre::Result<String> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| eyre::eyre!("can't get a page title from {}", path.display()))?;
+    Ok(stem.replace("%2F", "/"))
+}
+
+impl Publish {
+    /// The files to publish by page title.
+    fn pages(&self) -> color_eyre::Result<BTreeMap<String, PathBuf>> {
+        let mut files = vec![];
+        for path in &self.paths {
+            if path.is_dir() {
+                let mut entries = std::fs::read_dir(path)
+                    .wrap_err_with(|| format!("couldn't read {}", path.display()))?
+                    .map(|e| e.map(|e| e.path()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                entries.retain(|p| p.extension().is_some_and(|e| e == "wiki"));
+                files.extend(entries);
+            } else {
+                files.push(path.clone());
+            }
+        }
+        if let Some(title) = &self.title {
+            let [file] = files.as_slice() else {
+                eyre::bail!("--title needs exactly one file, got {}", files.len());
+            };
+            return Ok(BTreeMap::from([(title.clone(), file.clone())]));
+        }
+        let mut pages = BTreeMap::new();
+        for file in files {
+            let title = title_of(&file)?;
+            if let Some(other) = pages.insert(title.clone(), file.clone()) {
+                eyre::bail!(
+                    "{} and {} would both edit {title}",
+                    other.display(),
+                    file.display()
+                );
+            }
+        }
+        Ok(pages)
+    }
+
+    /// Publish the file at `path` to `title`, merged into the current text with `--merge`.
+    async fn publish(
+        &self,
+        wiki: &Wiki,
+        title: &str,
+        path: &Path,
+        summary: &str,
+    ) -> color_eyre::Result<Outcome> {
+        let mut text = std::fs::read_to_string(path)?;
+        let current = wiki.fetch(title).await?;
+        if let (true, Some(current)) = (self.merge, &current.text) {
+            let merged = merge::merge(current, &text)?;
+            merged.warn(title);
+            text = merged.text;
+        }
+        if self.dry_run {
+            Ok(self.mode.outcome(current.text.as_deref(), &text))
+        } else {
+            wiki.edit(title, &current, &text, summary, self.mode).await
+        }
+    }
+
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        config: &Config,
+        verbose: bool,
+    ) -> color_eyre::Result<()> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(self._run(stationpedia, config, verbose))
+    }
+
+    async fn _run(
+        &self,
+        stationpedia: &Stationpedia,
+        config: &Config,
+        verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let pages = self.pages()?;
+        if pages.is_empty() {
+            eyre::bail!("no .wiki files to publish");
+        }
+        let summary = self
+            .summary
+            .clone()
+            .or_else(|| config.bot.summary.clone())
+            .unwrap_or_else(|| match &stationpedia.version {
+                Some(version) => format!("Update generated content from Stationeers {version}"),
+                None => "Update generated content".to_owned(),
+            });
+        let wiki = Wiki::connect(config, self.api_url.as_deref(), !self.dry_run).await?;
+
+        let mut counts = BTreeMap::<Outcome, usize>::new();
+        let mut errored = vec![];
+        for (title, path) in &pages {
+            if verbose {
+                eprintln!("publishing {} to {title}", path.display());
+            }
+            let result = self.publish(&wiki, title, path, &summary).await;
+            match result {
+                Ok(outcome) => {
+                    println!("{outcome:9} {title}");
+                    *counts.entry(outcome).or_default() += 1;
+                }
+                Err(e) => errored.push((title, e)),
+            }
+        }
+
+        let summary = counts
+            .iter()
+            .map(|(outcome, n)| format!("{n} {outcome}"))
+            .collect::<Vec<_>>();
+        if self.dry_run {
+            eprintln!("Dry run, nothing was edited: {}", summary.join(", "));
+        } else {
+            eprintln!("{}", summary.join(", "));
+        }
+        if let Some(n) = counts.get(&Outcome::Conflict) {
+            eprintln!("{n} pages were edited while publishing and were left alone, run again to retry them");
+        }
+        if !errored.is_empty() {
+            eprintln!("{} pages errored:", errored.len());
+            for (title, e) in &errored {
+                eprintln!("  {title}: {e:#}");
+            }
+            eyre::bail!(
+                "failed to publish {} of {} pages",
+                errored.len(),
+                pages.len()
+            );
+        }
+        if counts.contains_key(&Outcome::Conflict) {
+            eyre::bail!("some pages had edit conflicts");
+        }
+        Ok(())
+    }
+}
@@ -1,20 +1,24 @@
 #![cfg(feature = "mw")]
-//! Query items on wiki
+//! Query pages on the wiki
 
 use color_eyre::eyre;
 
-use crate::stationpedia::Stationpedia;
+use crate::{config::Config, stationpedia::Stationpedia, wiki::Wiki};
 
 #[derive(Debug, clap::Parser)]
 pub struct Query {
-    #[command(subcommand)]
-    sub: QuerySubcommand,
+    /// Titles of the pages to print the wikitext of
+    #[arg(required = true)]
+    titles: Vec<String>,
+    /// API endpoint to use instead of `[bot] api_url`
+    #[arg(long)]
+    api_url: Option<String>,
 }
 impl Query {
     pub(crate) fn run(
         &self,
         stationpedia: &Stationpedia,
-        config: &toml_edit::DocumentMut,
+        config: &Config,
         verbose: bool,
     ) -> color_eyre::Result<()> {
         tokio::runtime::Builder::new_current_thread()
@@ -25,26 +29,20 @@ impl Query {
     pub(crate) async fn _run(
         &self,
         _stationpedia: &Stationpedia,
-        config: &toml_edit::DocumentMut,
+        config: &Config,
         _verbose: bool,
     ) -> color_eyre::Result<()> {
-        let client = mwapi::Client::builder(
-            config["bot"]["api_url"]
-                .as_str()
-                .ok_or_else(|| eyre::eyre!("No api_url in config"))?,
-        )
-        .build()
-        .await?;
-        dbg!(
-            client
-                .get_value(&[("action", "query"), ("prop", "info"), ("titles", "MIPS")])
-                .await?
-        );
+        let wiki = Wiki::connect(config, self.api_url.as_deref(), false).await?;
+        let mut missing = vec![];
+        for title in &self.titles {
+            match wiki.wikitext(title).await? {
+                Some(text) => println!("{text}"),
+                None => missing.push(title.as_str()),
+            }
+        }
+        if !missing.is_empty() {
+            eyre::bail!("no such pages: {}", missing.join(", "));
+        }
         Ok(())
     }
 }
-
-#[derive(Debug, clap::Subcommand)]
-enum QuerySubcommand {
-    All,
-}
@@ -0,0 +1,182 @@
+//! Generates wiki pages for reagents, from `Stationpedia.reagents`.
+
+use std::{fmt::Write as _, path::PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::{
+    config::Config,
+    diagnostics::Diagnostics,
+    enums::Enums,
+    stationpedia::{Reagent, Stationpedia},
+    wikitext::{Layout, Template},
+};
+
+#[derive(Debug, clap::Parser)]
+pub struct ReagentPage {
+    /// Name of the reagent, e.g. `Iron` or `Oil`, its page name from `reagents.names`, or an item it comes from, e.g.
+    /// `Soy Oil`
+    #[arg(required_unless_present = "all")]
+    reagent: Option<String>,
+    /// Generate every reagent, writing them to `<out-dir>/<Title>.wiki`
+    #[arg(long, conflicts_with = "reagent", requires = "out_dir")]
+    all: bool,
+    /// Directory to write pages to when using `--all`
+    #[arg(long, requires = "all")]
+    out_dir: Option<PathBuf>,
+}
+
+/// Wiki page name of a reagent, from `reagents.names` in the config or the reagent name itself.
+fn reagent_title<'a>(config: &'a Config, name: &'a str) -> &'a str {
+    config.reagents.names.get(name).map_or(name, String::as_str)
+}
+
+/// The reagent called `query`, ignoring case: by its name, its `reagents.names` page, or an item it comes from, like
+/// `Soy Oil` for `Oil`.
+fn find_reagent<'a>(
+    pedia: &'a Stationpedia,
+    config: &Config,
+    query: &str,
+) -> Option<(&'a String, &'a Reagent)> {
+    let reagents = || pedia.reagents.iter();
+    reagents()
+        .find(|(name, _)| name.eq_ignore_ascii_case(query))
+        .or_else(|| {
+            reagents().find(|(name, _)| reagent_title(config, name).eq_ignore_ascii_case(query))
+        })
+        .or_else(|| {
+            reagents().find(|(_, reagent)| {
+                reagent.sources.iter().flatten().any(|(prefab_name, _)| {
+                    pedia
+                        .lookup_prefab_name(prefab_name)
+                        .is_some_and(|p| p.title.eq_ignore_ascii_case(query))
+                })
+            })
+        })
+}
+
+fn reagent_page(
+    pedia: &Stationpedia,
+    config: &Config,
+    name: &str,
+    reagent: &Reagent,
+    diagnostics: &mut Diagnostics,
+) -> color_eyre::Result<String> {
+    let title = reagent_title(config, name);
+    let unit = reagent.unit_suffix();
+    let mut template = Template::new("Reagent");
+    template
+        .param("name", title)
+        .param("hash", reagent.hash)
+        .param("unit", &reagent.unit);
+    let sources = reagent
+        .sources
+        .iter()
+        .flatten()
+        .map(|(prefab_name, amount)| {
+            let source = pedia
+                .lookup_prefab_name(prefab_name)
+                .map_or(prefab_name.as_str(), |p| p.title.as_str());
+            format!("[[{source}]] ({amount}{unit})")
+        })
+        .collect::<Vec<_>>();
+    if !sources.is_empty() {
+        template.param("sources", sources.join(", "));
+    }
+    let mut out = template.render(Layout::Pretty);
+    out.push('\n');
+
+    let uses = pedia.recipes_using_reagent(name).map(|r| (name, r));
+    if let Some(table) = super::wikibox::used_in_table(pedia, config, name, uses, diagnostics)? {
+        write!(out, "\n== Used in ==\n{table}\n")?;
+    }
+
+    let mut processed_by = pedia
+        .pages
+        .iter()
+        .filter(|p| {
+            p.resource_consumer
+                .as_ref()
+                .is_some_and(|rc| rc.processed_reagents.contains(&reagent.hash))
+        })
+        .map(|p| p.title.as_str())
+        .collect::<Vec<_>>();
+    processed_by.sort();
+    processed_by.dedup();
+    if !processed_by.is_empty() {
+        out.push_str("\n== Processed by ==\n");
+        for device in processed_by {
+            writeln!(out, "* [[{device}]]")?;
+        }
+    }
+    Ok(out)
+}
+
+impl ReagentPage {
+    /// Write a page for every reagent into `--out-dir`, reporting the reagents that failed at the end.
+    fn run_all(&self, stationpedia: &Stationpedia, config: &Config) -> color_eyre::Result<()> {
+        let out_dir = self
+            .out_dir
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("--all requires --out-dir"))?;
+        std::fs::create_dir_all(out_dir)?;
+        let mut diagnostics = Diagnostics::default();
+        let mut written = 0;
+        let mut errored = vec![];
+        for (name, reagent) in &stationpedia.reagents {
+            let path = out_dir.join(format!(
+                "{}.wiki",
+                reagent_title(config, name).replace('/', "%2F")
+            ));
+            let result = reagent_page(stationpedia, config, name, reagent, &mut diagnostics)
+                .and_then(|page| {
+                    std::fs::write(&path, super::provenance(stationpedia) + &page)
+                        .wrap_err_with(|| format!("couldn't write {}", path.display()))
+                });
+            match result {
+                Ok(()) => written += 1,
+                Err(e) => errored.push((name, e)),
+            }
+        }
+        eprintln!("Wrote {written} reagents to {}", out_dir.display());
+        diagnostics.report();
+        if !errored.is_empty() {
+            eprintln!("{} reagents errored:", errored.len());
+            for (name, e) in &errored {
+                eprintln!("  {name}: {e:#}");
+            }
+            eyre::bail!(
+                "failed {} of {} reagents",
+                errored.len(),
+                stationpedia.reagents.len()
+            );
+        }
+        Ok(())
+    }
+
+    pub(crate) fn run(
+        &self,
+        stationpedia: &Stationpedia,
+        _enums: &Enums,
+        config: &Config,
+        _verbose: bool,
+    ) -> color_eyre::Result<()> {
+        if self.all {
+            return self.run_all(stationpedia, config);
+        }
+        let name = self
+            .reagent
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("no reagent given"))?;
+        let (name, reagent) = find_reagent(stationpedia, config, name)
+            .ok_or_else(|| eyre::eyre!("no reagent named `{name}`"))?;
+        let mut diagnostics = Diagnostics::default();
+        print!("{}", super::provenance(stationpedia));
+        print!(
+            "{}",
+            reagent_page(stationpedia, config, name, reagent, &mut diagnostics)?
+        );
+        diagnostics.report();
+        Ok(())
+    }
+}
@@ -1,20 +1,72 @@
 //! Generates a wiki box for a given item.
 
-use std::fmt::Write as _;
+use std::{fmt::Write as _, path::PathBuf};
+
+use color_eyre::eyre::{self, WrapErr};
+
+mod connections;
+mod memory;
+mod page;
+mod products;
+mod slots;
+mod used_in;
+
+pub(crate) use used_in::used_in_table;
 
 use crate::{
-    enums::Enums,
-    stationpedia::{Page, Stationpedia},
+    config::{Config, Values},
+    diagnostics::{Diagnostics, Missing, Section},
+    enums::{Enums, LogicTypeInfo, Source},
+    stationpedia::{Page, Recipe, Stationpedia},
+    wikitext::{Layout, Template},
 };
 
 #[derive(Debug, clap::Parser)]
 pub struct Wikibox {
-    item: String,
+    /// Key, title, prefab name or prefab hash of the page
+    #[arg(required_unless_present = "all")]
+    item: Option<String>,
+    /// Generate every page in the stationpedia, writing them to `<out-dir>/<Title>.wiki`
+    #[arg(long, conflicts_with = "item", requires = "out_dir")]
+    all: bool,
+    /// Only generate pages where the key, title or prefab name matches this regex
+    #[arg(long, requires = "all")]
+    filter: Option<regex::Regex>,
+    /// Directory to write pages to when using `--all`
+    #[arg(long, requires = "all")]
+    out_dir: Option<PathBuf>,
+    /// Assemble a complete article using the `[page]` skeleton in the config, instead of loose fragments
+    #[arg(long)]
+    page: bool,
 }
 
 impl Page {
-    fn structure(&self, pedia: &Stationpedia) -> color_eyre::Result<Option<String>> {
-        let mut out = String::new();
+    /// Title of the page with `prefab_name`, or the prefab name itself if there is no such page.
+    fn title_of<'a>(
+        &self,
+        pedia: &'a Stationpedia,
+        prefab_name: &'a str,
+        section: Section,
+        diagnostics: &mut Diagnostics,
+    ) -> &'a str {
+        match pedia.lookup_prefab_name(prefab_name) {
+            Some(page) => &page.title,
+            None => {
+                diagnostics.push(
+                    &self.key,
+                    section,
+                    Missing::PrefabName(prefab_name.to_owned()),
+                );
+                prefab_name
+            }
+        }
+    }
+
+    pub(crate) fn structure(
+        &self,
+        pedia: &Stationpedia,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
         let Page {
             structure,
             prefab_hash,
@@ -27,17 +79,14 @@ impl Page {
             return Ok(None);
         };
 
-        out.push_str(&textwrap::dedent(&format!(
-            "
-                {{{{Structurebox
-                | name = {title}
-                | image = [[File:{prefab_name}.png]]
-                | prefab_hash = {prefab_hash}
-                | prefab_name = {prefab_name}\n"
-        )));
-
+        let mut template = Template::new("Structurebox");
+        template
+            .param("name", title)
+            .param("image", format!("[[File:{prefab_name}.png]]"))
+            .param("prefab_hash", prefab_hash)
+            .param("prefab_name", prefab_name);
         if let Some(power) = base_power_draw {
-            writeln!(out, "| power_usage = {power}")?;
+            template.param("power_usage", power);
         }
 
         let grid = if structure.small_grid {
@@ -45,19 +94,31 @@ impl Page {
         } else {
             "Large"
         };
-        writeln!(out, "| placed_on_grid = {grid} Grid")?;
+        template.param("placed_on_grid", format!("{grid} Grid"));
         let mut rec = None;
         for (count, state) in structure.build_states.0.iter().enumerate() {
             let rcount = count + 1;
-            if let Some(exit) = &state.tool_exit {
+            if let Some(exit) = state.tool_exit.as_ref().filter(|e| !e.is_empty()) {
                 if exit.len() > 1 {
-                    panic!()
+                    diagnostics.push(
+                        &self.key,
+                        Section::Structure,
+                        Missing::Unexpected(format!(
+                            "build state {rcount} has {} deconstruction tools",
+                            exit.len()
+                        )),
+                    );
                 }
-                let tool = &pedia
-                    .lookup_prefab_name(&exit[0].prefab_name)
-                    .unwrap()
-                    .title;
-                writeln!(out, "| decon_with_tool{rcount} = [[{tool}]]")?;
+                let tools = exit
+                    .iter()
+                    .map(|t| {
+                        let tool =
+                            self.title_of(pedia, &t.prefab_name, Section::Structure, diagnostics);
+                        format!("[[{tool}]]")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                template.param(&format!("decon_with_tool{rcount}"), tools);
             }
             if let Some(tool) = &state.tool {
                 // to always get the tool, we sort by is_tool
@@ -66,59 +127,88 @@ impl Page {
                 let has_tool = tools.iter().any(|t| t.is_tool);
                 if count == 0 {
                     if has_tool {
-                        panic!()
+                        diagnostics.push(
+                            &self.key,
+                            Section::Structure,
+                            Missing::Unexpected(
+                                "placing requires a tool, only the item is shown".to_owned(),
+                            ),
+                        );
+                    }
+                    if let Some(placed) = tool.iter().find(|t| !t.is_tool).or(tool.first()) {
+                        let placed_with_item = self.title_of(
+                            pedia,
+                            &placed.prefab_name,
+                            Section::Structure,
+                            diagnostics,
+                        );
+                        template
+                            .param("placed_with_item", format!("[[{placed_with_item}]]"))
+                            .param("item_rec1", format!("[[{placed_with_item}]]"));
                     }
-                    let placed_with_item = &pedia
-                        .lookup_prefab_name(&tool[0].prefab_name)
-                        .unwrap()
-                        .title;
-                    writeln!(out, "| placed_with_item = [[{placed_with_item}]]")?;
-                    writeln!(out, "| item_rec1 = [[{placed_with_item}]]")?;
                 } else if has_tool {
                     if let Some(tool1) = tool.first() {
-                        let name = &pedia.lookup_prefab_name(&tool1.prefab_name).unwrap().title;
-                        if let Some(quantity) = tool1.quantity {
-                            writeln!(out, "| const_with_tool{count} = {quantity} x [[{name}]]")?;
-                        } else {
-                            writeln!(out, "| const_with_tool{count} = [[{name}]]")?;
-                        }
+                        let name = self.title_of(
+                            pedia,
+                            &tool1.prefab_name,
+                            Section::Structure,
+                            diagnostics,
+                        );
+                        let tool = match tool1.quantity {
+                            Some(quantity) => format!("{quantity} x [[{name}]]"),
+                            None => format!("[[{name}]]"),
+                        };
+                        template.param(&format!("const_with_tool{count}"), tool);
                         rec = None;
                     }
                     if let Some(tool2) = tool.get(1) {
-                        let name = &pedia.lookup_prefab_name(&tool2.prefab_name).unwrap().title;
-                        if let Some(quantity) = tool2.quantity {
-                            writeln!(out, "| const_with_item{count} = {quantity} x [[{name}]]")?;
-                            rec = Some(format!("{quantity} x [[{name}]]"));
-                        } else {
-                            writeln!(out, "| const_with_item{count} = [[{name}]]")?;
-                            rec = Some(format!("[[{name}]]"));
-                        }
+                        let name = self.title_of(
+                            pedia,
+                            &tool2.prefab_name,
+                            Section::Structure,
+                            diagnostics,
+                        );
+                        let item = match tool2.quantity {
+                            Some(quantity) => format!("{quantity} x [[{name}]]"),
+                            None => format!("[[{name}]]"),
+                        };
+                        template.param(&format!("const_with_item{count}"), &item);
+                        rec = Some(item);
                     }
                 } else if let Some(tool1) = tool.first() {
-                    let name = &pedia.lookup_prefab_name(&tool1.prefab_name).unwrap().title;
-                    if let Some(quantity) = tool1.quantity {
-                        writeln!(out, "| const_with_item{count} = {quantity} x [[{name}]]")?;
-                        rec = Some(format!("{quantity} x [[{name}]]"));
-                    } else {
-                        writeln!(out, "| const_with_item{count} = [[{name}]]")?;
-                        rec = Some(format!("[[{name}]]"));
-                    }
+                    let name =
+                        self.title_of(pedia, &tool1.prefab_name, Section::Structure, diagnostics);
+                    let item = match tool1.quantity {
+                        Some(quantity) => format!("{quantity} x [[{name}]]"),
+                        None => format!("[[{name}]]"),
+                    };
+                    template.param(&format!("const_with_item{count}"), &item);
+                    rec = Some(item);
                     if tool.len() > 1 {
-                        panic!()
+                        diagnostics.push(
+                            &self.key,
+                            Section::Structure,
+                            Missing::Unexpected(format!(
+                                "build state {rcount} uses {} items, only the first is shown",
+                                tool.len()
+                            )),
+                        );
                     }
                 }
             }
             if let Some(rec) = &rec {
-                writeln!(out, "| item_rec{rcount} = {rec}")?;
+                template.param(&format!("item_rec{rcount}"), rec);
             }
         }
-        write!(out, "}}}}")?;
-
-        Ok(Some(out))
+        Ok(Some(template.render(Layout::Pretty)))
     }
 
-    pub fn item(&self, pedia: &Stationpedia) -> color_eyre::Result<Option<String>> {
-        let mut out = String::new();
+    pub fn item(
+        &self,
+        pedia: &Stationpedia,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
         let Page {
             constructs,
             item,
@@ -131,60 +221,42 @@ impl Page {
         let Some(item) = item else {
             return Ok(None);
         };
-        out.push_str(
-            textwrap::dedent(&format!(
-                "
-                {{{{Itembox
-                | name = {title}
-                | image = [[File:{prefab_name}.png]]
-                | prefabhash = {prefab_hash}
-                | prefabname = {prefab_name}
-            "
-            ))
-            .trim_start(),
-        );
-        writeln!(out, "| stacks = {}", item.max_quantity.unwrap_or(1.0))?;
-
-        writeln!(out, "| slot_class = SlotClass.{}", item.slot_class)?;
-        writeln!(out, "| sorting_class = SortingClass.{}", item.sorting_class)?;
+        let mut template = Template::new("Itembox");
+        template
+            .param("name", title)
+            .param("image", format!("[[File:{prefab_name}.png]]"))
+            .param("prefabhash", prefab_hash)
+            .param("prefabname", prefab_name)
+            .param("stacks", item.max_quantity.unwrap_or(1.0))
+            .param("slot_class", format!("SlotClass.{}", item.slot_class))
+            .param(
+                "sorting_class",
+                format!("SortingClass.{}", item.sorting_class),
+            );
 
         let mut count = 1;
         for recipe in &item.recipes {
-            let mut ingredients = String::new();
-            for (i, (ingredient, quantity)) in recipe
-                .reagents
-                .iter()
-                .filter(|(_, q)| *q > &0.0)
-                .enumerate()
-            {
-                let name = &pedia
-                    .lookup_prefab_name(ingredient)
-                    .map(|i| &i.title)
-                    .unwrap_or(ingredient);
-                if i > 0 {
-                    ingredients.push_str(", ");
-                }
-                let (amount, ingredient) = recipe_amount(pedia, name, &recipe.creator_prefab_name);
-                let ingredient = ingredient
-                    .iter()
-                    .map(|ingr| format!("[[{ingr}]]"))
-                    .collect::<Vec<_>>();
-                write!(ingredients, "{quantity}{amount} {}", ingredient.join(", "))?;
-            }
+            let ingredients = recipe_ingredients(pedia, config, recipe)?;
             if recipe.creator_prefab_name == "StructureOrganicsPrinter" {
                 continue;
             }
-            let creator = &pedia
-                .lookup_prefab_name(&recipe.creator_prefab_name)
-                .unwrap()
-                .title;
+            let creator = self.title_of(
+                pedia,
+                &recipe.creator_prefab_name,
+                Section::Item,
+                diagnostics,
+            );
             let tier = if recipe.tier_name == "TierTwo" {
                 " (Tier Two)"
             } else {
                 ""
             };
-            writeln!(out, "| recipe_machine{count} = {creator}{tier}",)?;
-            writeln!(out, "| recipe_cost{count} = {ingredients}",)?;
+            template
+                .param(
+                    &format!("recipe_machine{count}"),
+                    format!("{creator}{tier}"),
+                )
+                .param(&format!("recipe_cost{count}"), ingredients);
             count += 1;
         }
 
@@ -193,33 +265,47 @@ impl Page {
             let contructs = constructs
                 .iter()
                 .map(|c| {
-                    let name = &pedia.lookup_key(&c.page_link).unwrap().title;
+                    let name = match pedia.lookup_key(&c.page_link) {
+                        Some(page) => &page.title,
+                        None => {
+                            diagnostics.push(
+                                &self.key,
+                                Section::Item,
+                                Missing::Key(c.page_link.clone()),
+                            );
+                            &c.name_of_thing
+                        }
+                    };
                     format!("[[{name}]]")
                 })
                 .collect::<Vec<_>>()
                 .join(", ");
 
-            writeln!(out, "| constructs = {contructs}")?;
+            template.param("constructs", contructs);
         }
 
         if let Some(food) = &item.food {
             if food.nutrition_value.is_some_and(|v| v > 0.0) {
-                writeln!(out, "| nutrition = {}", food.nutrition_value.unwrap())?;
-                writeln!(out, "| quality = {}", food.nutrition_quality_readable)?;
+                template
+                    .param("nutrition", food.nutrition_value.unwrap())
+                    .param("quality", &food.nutrition_quality_readable);
                 if let Some(bonus) = food.mood_bonus.filter(|v| v != &0.0) {
-                    writeln!(out, "| moodbonus = {}%", bonus * 100.0)?;
+                    template.param("moodbonus", format!("{}%", bonus * 100.0));
                 }
             }
         }
         if let Some(growth_time) = &growth_time {
-            writeln!(out, "| growthtime = {}", growth_time)?;
+            template.param("growthtime", growth_time);
         }
-        write!(out, "}}}}")?;
-        Ok(Some(out))
+        Ok(Some(template.render(Layout::Pretty)))
     }
 
-    pub fn item_recipe(&self, pedia: &Stationpedia) -> color_eyre::Result<Option<String>> {
-        let mut out = String::new();
+    pub fn item_recipe(
+        &self,
+        pedia: &Stationpedia,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
         let Page { item, .. } = &self;
         let Some(item) = item else {
             return Ok(None);
@@ -232,58 +318,38 @@ impl Page {
         if item.recipes.is_empty() {
             return Ok(None);
         }
-        out.push_str(
-            textwrap::dedent(
-                "
-                == Recipes ==
-                {{Recipe
-            ",
-            )
-            .trim(),
-        );
+        let mut template = Template::new("Recipe");
         for recipe in &item.recipes {
-            let mut ingredients = String::new();
-            for (i, (ingredient, quantity)) in recipe
-                .reagents
-                .iter()
-                .filter(|(_, q)| *q > &0.0)
-                .enumerate()
-            {
-                if i > 0 {
-                    ingredients.push_str(", ");
-                }
-                let (amount, ingredient) =
-                    recipe_amount(pedia, ingredient, &recipe.creator_prefab_name);
-                let ingredient = ingredient
-                    .iter()
-                    .map(|ingr| format!("[[{ingr}]]"))
-                    .collect::<Vec<_>>();
-                write!(ingredients, "{quantity}{amount} {}", ingredient.join(", "))?;
-            }
+            let ingredients = recipe_ingredients(pedia, config, recipe)?;
             if recipe.creator_prefab_name == "StructureOrganicsPrinter" {
                 continue;
             }
-            let creator = &pedia
-                .lookup_prefab_name(&recipe.creator_prefab_name)
-                .unwrap()
-                .title;
+            let creator = self.title_of(
+                pedia,
+                &recipe.creator_prefab_name,
+                Section::Recipe,
+                diagnostics,
+            );
             let tier = if recipe.tier_name == "TierTwo" {
                 " (Tier Two)"
             } else {
                 ""
             };
-            let time = recipe.time;
-            let energy = recipe.energy;
-            write!(out, "\n|{{{{Recipe/row |machine = {creator}{tier} |mats = {ingredients} |time = {time} |energy = {energy}}}}}")?;
+            let row = Template::new("Recipe/row")
+                .param("machine", format!("{creator}{tier}"))
+                .param("mats", ingredients)
+                .param("time", recipe.time)
+                .param("energy", recipe.energy)
+                .render(Layout::Spaced);
+            template.positional(row);
         }
-        write!(out, "\n}}}}")?;
-        Ok(Some(out))
+        Ok(Some(template.render(Layout::Pretty)))
     }
 
     pub fn description(
         &self,
         pedia: &Stationpedia,
-        config: &toml_edit::DocumentMut,
+        config: &Config,
     ) -> color_eyre::Result<Option<String>> {
         if self.description.is_empty() {
             return Ok(None);
@@ -307,9 +373,9 @@ impl Page {
         &self,
         pedia: &Stationpedia,
         enums: &Enums,
-        config: &toml_edit::DocumentMut,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
     ) -> color_eyre::Result<Option<String>> {
-        let mut out = String::new();
         let Page {
             logic_info: Some(logic_info),
             mode_insert,
@@ -319,65 +385,12 @@ impl Page {
             return Ok(None);
         };
 
-        let mut replacements_global: Vec<(regex::Regex, &str, i64)> = vec![];
-        if let Some(replace) = config
-            .get("logic")
-            .and_then(|i| i.get("replace"))
-            .and_then(|r| r.as_array_of_tables())
-            .map(|a| {
-                a.iter().filter_map(|a| {
-                    Some((
-                        a.get("regex")?.as_str()?,
-                        a.get("replace")?.as_str()?,
-                        a.get("prio").and_then(|p| p.as_integer()),
-                    ))
-                })
-            })
-        {
-            replacements_global
-                .extend(replace.map(|(r, rpl, p)| {
-                    (regex::Regex::new(r).unwrap(), rpl, p.unwrap_or_default())
-                }));
-        }
-
-        out.push_str("{{Data Network Header}}\n");
+        let mut out = Template::new("Data Network Header").render(Layout::Compact);
+        out.push('\n');
 
         if !logic_info.logic_types.types.is_empty() {
-            out.push_str("{{Data Parameters|");
-            let mut replacements = vec![];
-
-            if let Some(replace) = config
-                .get("logic")
-                .and_then(|e| e.get("device"))
-                .and_then(|t| t.get(&self.prefab_name))
-                .and_then(|i| i.get("replace"))
-                .and_then(|r| r.as_array())
-                .map(|a| {
-                    a.iter().filter_map(|a| {
-                        let a = a.as_inline_table()?;
-                        Some((
-                            a.get("regex")?.as_str()?,
-                            a.get("replace")?.as_str()?,
-                            a.get("prio").and_then(|p| p.as_integer()),
-                        ))
-                    })
-                })
-            {
-                replacements.extend(replace.map(|(r, rpl, p)| {
-                    std::borrow::Cow::Owned((
-                        regex::Regex::new(r).unwrap(),
-                        rpl,
-                        p.unwrap_or_default(),
-                    ))
-                }));
-            }
-            replacements.extend(
-                replacements_global
-                    .iter()
-                    .map(std::borrow::Cow::Borrowed)
-                    .collect::<Vec<_>>(),
-            );
-            replacements.sort_by_key(|p| p.2);
+            let replacements = logic_replacements(config, &self.prefab_name)?;
+            let mut rows = vec![];
             for (logic_type, rw) in logic_info.logic_types.types.iter() {
                 if enums
                     .script_enums
@@ -388,129 +401,108 @@ impl Page {
                     continue;
                 }
                 // {{Data Parameters/row|Mode|0|a}}
-                write!(out, "\n{{{{Data Parameters/row|{logic_type}")?;
-                let conf_global = config
-                    .get("logic")
-                    .and_then(|e| e.get("types"))
-                    .and_then(|t| t.get(logic_type));
-                let conf_device = config
-                    .get("logic")
-                    .and_then(|e| e.get("device"))
-                    .and_then(|t| t.get(&self.prefab_name))
-                    .and_then(|t| t.get(logic_type));
-
-                let ty: &str;
-                if let Some(typ) = conf_global.and_then(|i| i.get("type")) {
-                    ty = typ.as_str().unwrap();
-                } else if logic_type.contains("Ratio") || logic_type.contains("Pressure") {
-                    ty = "Float";
-                } else {
-                    ty = "Integer";
+                let mut row = Template::new("Data Parameters/row");
+                row.positional(logic_type);
+                let conf_global = config.logic.types.get(logic_type.as_str());
+                let conf_device = config.device_logic_type(&self.prefab_name, logic_type);
+
+                let info =
+                    LogicTypeInfo::resolve(enums, config, Some(&self.prefab_name), logic_type)?;
+                if info.source == Source::Heuristic {
+                    tracing::debug!("type of {logic_type} guessed from its name");
                 }
-                write!(out, "|{ty}")?;
+                row.positional(info.ty);
                 if !rw.contains("Read") {
-                    out.push_str("|r=0");
+                    row.param("r", 0);
                 }
                 if !rw.contains("Write") {
-                    out.push_str("|w=0");
+                    row.param("w", 0);
                 }
-                let wikify = |s: &str| -> color_eyre::Result<String> {
-                    let s = s.trim();
-                    let mut out = String::new();
-                    translate_to_wiki(&mut out, s, pedia, config)?;
-                    if s.contains('\n') {
-                        Ok(format!("<div>{}</div>", out.replace('\n', "<br>\n")))
-                    } else {
-                        Ok(out.to_string())
-                    }
-                };
-                let enum_desc = |out: &mut String| -> color_eyre::Result<()> {
-                    if let Some(lt) = enums
-                        .script_enums
-                        .get("LogicType")
-                        .and_then(|lt| lt.values.get(logic_type))
-                    {
-                        let mut desc = lt.description.clone();
-                        for replace in &replacements {
-                            desc = replace.0.replace_all(&desc, replace.1).to_string();
-                        }
-                        {}
-                        write!(out, "|{}", wikify(&desc)?)?;
-                    }
-                    Ok(())
-                };
-                if let Some(desc) = conf_device.and_then(|i| i.get("description")) {
-                    write!(out, "|{}", wikify(desc.as_str().unwrap())?)?;
-                } else if let Some(desc) = conf_global.and_then(|i| i.get("description")) {
-                    if let Some(desc) = desc.as_str() {
-                        write!(out, "|{}", wikify(desc)?)?;
-                    } else if let Some(table) = desc.as_table_like() {
-                        if let Some(desc) = table.get("default") {
-                            write!(out, "|{}", wikify(desc.as_str().unwrap())?)?;
+                let enum_desc =
+                    |row: &mut Template, diagnostics: &mut Diagnostics| -> color_eyre::Result<()> {
+                        if let Some(lt) = enums
+                            .script_enums
+                            .get("LogicType")
+                            .and_then(|lt| lt.values.get(logic_type))
+                        {
+                            let mut desc = lt.description.clone();
+                            for replace in &replacements {
+                                desc = replace.0.replace_all(&desc, replace.1).to_string();
+                            }
+                            row.positional(wikify(&desc, pedia, config)?);
                         } else {
-                            enum_desc(&mut out)?;
+                            diagnostics.push(
+                                &self.key,
+                                Section::Logic,
+                                Missing::Enum {
+                                    listing: "LogicType".to_owned(),
+                                    name: logic_type.clone(),
+                                },
+                            );
                         }
-                    }
+                        Ok(())
+                    };
+                if let Some(desc) = conf_device
+                    .and_then(|c| c.description.as_deref())
+                    .or_else(|| conf_global.and_then(|c| c.description.as_deref()))
+                {
+                    row.positional(wikify(desc, pedia, config)?);
                 } else {
-                    enum_desc(&mut out)?;
+                    enum_desc(&mut row, diagnostics)?;
                 }
-                'values: {
-                    'conf: {
-                        let mut values = None;
-                        if let Some(values_) = conf_device.and_then(|i| i.get("values")) {
-                            values = Some(values_);
-                        } else if let Some(values_) = conf_global.and_then(|i| i.get("values")) {
-                            if let Some(table) = values_.as_table_like() {
-                                if let Some(entry) = table.get("default") {
-                                    values = Some(entry);
-                                }
-                            } else {
-                                values = Some(values_);
-                            };
-                        }
-                        let Some(values) = values else {
-                            break 'conf;
-                        };
-                        if let Some(arr) = values.as_array() {
-                            if arr.is_empty() {
-                                break 'values;
-                            }
-                            write!(out, "|multiple={}", arr.len())?;
-                            for (e, v) in arr.iter().map(|v| v.as_str().unwrap()).enumerate() {
-                                write!(out, "|{e}|{v}")?;
+                let values = conf_device
+                    .and_then(|c| c.values.as_ref())
+                    .or_else(|| conf_global.and_then(|c| c.values.as_ref()));
+                match values {
+                    Some(Values::List(list)) => {
+                        if !list.is_empty() {
+                            row.param("multiple", list.len());
+                            for (e, v) in list.iter().enumerate() {
+                                row.positional(e).positional(v);
                             }
-                            break 'values;
-                        } else if let Some(str) = values.as_str() {
-                            write!(out, "|{}", str)?;
-                            break 'values;
-                        } else if let Some(table) = values.as_table_like() {
-                            if table.is_empty() {
-                                break 'values;
-                            }
-                            write!(out, "|multiple={}", table.len())?;
-                            for (k, v) in table.iter() {
-                                write!(out, "|{}|{}", k, v.as_str().unwrap())?;
+                        }
+                    }
+                    Some(Values::Map(map)) => {
+                        if !map.is_empty() {
+                            row.param("multiple", map.len());
+                            for (k, v) in map {
+                                row.positional(k).positional(v);
                             }
-                            break 'values;
                         }
                     }
-                    if logic_type == "Mode" && !mode_insert.is_empty() {
-                        write!(out, "|multiple={}", mode_insert.len())?;
-                        for (e, v) in mode_insert.iter().enumerate() {
-                            write!(out, "|{e}|{}", v.logic_name)?;
+                    Some(Values::Text(text)) => {
+                        row.positional(text);
+                    }
+                    None => {
+                        if logic_type == "Mode" && !mode_insert.is_empty() {
+                            row.param("multiple", mode_insert.len());
+                            for (e, v) in mode_insert.iter().enumerate() {
+                                row.positional(e).positional(&v.logic_name);
+                            }
+                        } else if !info.values.is_empty() {
+                            row.param("multiple", info.values.len());
+                            for (value, name) in &info.values {
+                                row.positional(value).positional(name);
+                            }
+                        } else if let Some(range) = &info.range {
+                            row.positional(range);
                         }
-                    } else if ty == "Boolean" {
-                        write!(out, "|0 or 1")?;
-                    } else if logic_type.contains("Ratio") {
-                        write!(out, "|0.0 to 1.0")?;
                     }
                 }
-
-                out.push_str("}}");
+                if let Some(unit) = &info.unit {
+                    row.param("unit", unit);
+                }
+                rows.push(row);
             }
-            out.push_str("\n}}\n");
+            out.push_str(&with_rows("Data Parameters", &rows));
+            out.push('\n');
         } else {
-            out.push_str("|{{Data Parameters|empty=}}\n");
+            out.push_str(
+                &Template::new("Data Parameters")
+                    .param("empty", "")
+                    .render(Layout::Compact),
+            );
+            out.push('\n');
         }
         out = out.replace("{device}", &self.title);
 
@@ -518,11 +510,59 @@ impl Page {
     }
 }
 
+/// Description replacements for a device, from `logic.device.<prefab>.replace` and `logic.replace`, ordered by priority.
+fn logic_replacements<'a>(
+    config: &'a Config,
+    prefab_name: &str,
+) -> color_eyre::Result<Vec<(regex::Regex, &'a str, i64)>> {
+    let device = config
+        .logic
+        .device
+        .get(prefab_name)
+        .map(|d| d.replace.as_slice())
+        .unwrap_or_default();
+    let mut replacements = device
+        .iter()
+        .chain(&config.logic.replace)
+        .map(|r| {
+            let regex = regex::Regex::new(r.regex.get_ref()).map_err(|e| {
+                eyre::eyre!("{}: invalid regex: {e}", config.location(r.regex.span()))
+            })?;
+            Ok((regex, r.replace.as_str(), r.prio))
+        })
+        .collect::<color_eyre::Result<Vec<_>>>()?;
+    replacements.sort_by_key(|p| p.2);
+    Ok(replacements)
+}
+
+/// A call of the template `name` like `{{Slots|`, with `rows` as its only parameter, one per line.
+fn with_rows(name: &str, rows: &[Template]) -> String {
+    let rows = rows
+        .iter()
+        .map(|row| format!("\n{}", row.render(Layout::Compact)))
+        .collect::<String>();
+    Template::new(name)
+        .positional(format!("{rows}\n"))
+        .render(Layout::Compact)
+}
+
+/// Translate a logic description to wikitext, multiline descriptions are wrapped in a `<div>`.
+pub(crate) fn wikify(s: &str, pedia: &Stationpedia, config: &Config) -> color_eyre::Result<String> {
+    let s = s.trim();
+    let mut out = String::new();
+    translate_to_wiki(&mut out, s, pedia, config)?;
+    if s.contains('\n') {
+        Ok(format!("<div>{}</div>", out.replace('\n', "<br>\n")))
+    } else {
+        Ok(out.to_string())
+    }
+}
+
 fn translate_to_wiki(
     out: &mut String,
     string: &str,
     pedia: &Stationpedia,
-    config: &toml_edit::DocumentMut,
+    config: &Config,
 ) -> color_eyre::Result<()> {
     let re = regex::Regex::new(r"<color=.*?>|</color>").unwrap();
     let string = re.replace_all(string, "").to_string();
@@ -543,12 +583,7 @@ fn translate_to_wiki(
                 tracing::warn!("got wierd link: {}", link);
                 continue;
             };
-            if let Some(link) = config
-                .get("stationpedia")
-                .and_then(|c| c.get("links"))
-                .and_then(|c| c.get(thing))
-                .and_then(|c| c.as_str())
-            {
+            if let Some(link) = config.link(thing) {
                 if link == link_display {
                     s.push_str(&format!("[[{link}]]"));
                 } else {
@@ -575,86 +610,190 @@ fn translate_to_wiki(
     Ok(())
 }
 
+/// Unit and wiki page names for an amount of `reagent` in a recipe made by `creator_prefab_name`.
+///
+/// Names come from `reagents.names` in the config, otherwise from the items in `Reagent.Sources` that the creator
+/// accepts. Ingots are named after their reagent, as the wiki does. The unit comes from `Reagent.Unit`, unless
+/// `reagents.units` in the config has one for the first name, like items that are counted rather than weighed.
 fn recipe_amount<'a>(
     pedia: &'a Stationpedia,
-    ingredient: &'a str,
+    config: &'a Config,
+    reagent: &'a str,
+    creator_prefab_name: &str,
+) -> (&'a str, Vec<&'a str>) {
+    let names = recipe_names(pedia, config, reagent, creator_prefab_name);
+    let unit = config
+        .reagents
+        .units
+        .get(names[0])
+        .map(String::as_str)
+        .or_else(|| pedia.reagents.get(reagent).map(|r| r.unit_suffix()))
+        .unwrap_or(" x");
+    (unit, names)
+}
+
+fn recipe_names<'a>(
+    pedia: &'a Stationpedia,
+    config: &'a Config,
+    reagent: &'a str,
     creator_prefab_name: &str,
-) -> (&'static str, Vec<&'a str>) {
-    // FIXME: Use correct ingredient name, Soy is for example really Soybean, Steel could be Can, etc etc
-    let ingredient = pedia
+) -> Vec<&'a str> {
+    if let Some(name) = config.reagents.names.get(reagent) {
+        return vec![name];
+    }
+    let sources = pedia.reagents.get(reagent).and_then(|r| r.sources.as_ref());
+    let names = pedia
         .lookup_prefab_name(creator_prefab_name)
         .and_then(|p| p.resource_consumer.as_ref())
-        .map(|rc| {
-            rc.consumed_resources.iter().filter_map(|i| {
-                let ingr = pedia.lookup_prefab_name(i)?;
-                ingr.item
-                    .as_ref()?
-                    .reagents
-                    .as_ref()?
-                    .get(ingredient)
-                    .filter(|a| a >= &&1.0)
-                    .map(move |_| {
-                        if !ingr.title.contains("Ingot (") {
-                            &ingr.title
-                        } else {
-                            ingredient
-                        }
-                    })
-            })
+        .into_iter()
+        .flat_map(|rc| &rc.consumed_resources)
+        .filter(|prefab| {
+            sources
+                .and_then(|s| s.get(*prefab))
+                .is_some_and(|a| *a >= 1.0)
         })
-        .map(|i| i.collect())
-        .unwrap_or(vec![ingredient]);
-    let amount = match ingredient[0] {
-        "Iron" | "Gold" | "Carbon" | "Uranium" | "Copper" | "Steel" | "Hydrocarbon" | "Silver"
-        | "Electrum" | "Invar" | "Constantan" | "Solder" | "Silicon" | "Waspaloy" | "Stellite"
-        | "Inconel" | "Hastelloy" | "Astroloy" | "Cobalt" | "Flour" => "g",
-        "Milk" | "Soy Oil" => "ml",
-        _ => " x",
-    };
-    (amount, ingredient)
+        .filter_map(|prefab| pedia.lookup_prefab_name(prefab))
+        .map(|source| {
+            if source.title.contains("Ingot (") {
+                reagent
+            } else {
+                source.title.as_str()
+            }
+        })
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        vec![reagent]
+    } else {
+        names
+    }
+}
+
+/// The reagents of a recipe as wiki text, e.g. `10g [[Iron]], 2g [[Copper]]`.
+fn recipe_ingredients(
+    pedia: &Stationpedia,
+    config: &Config,
+    recipe: &Recipe,
+) -> color_eyre::Result<String> {
+    let mut ingredients = String::new();
+    for (i, (ingredient, quantity)) in recipe
+        .reagents
+        .iter()
+        .filter(|(_, q)| *q > &0.0)
+        .enumerate()
+    {
+        if i > 0 {
+            ingredients.push_str(", ");
+        }
+        let (amount, ingredient) =
+            recipe_amount(pedia, config, ingredient, &recipe.creator_prefab_name);
+        let ingredient = ingredient
+            .iter()
+            .map(|ingr| format!("[[{ingr}]]"))
+            .collect::<Vec<_>>();
+        write!(ingredients, "{quantity}{amount} {}", ingredient.join(", "))?;
+    }
+    Ok(ingredients)
+}
+
+impl Page {
+    /// All wikibox sections for this page, separated by blank lines.
+    pub fn wikibox(
+        &self,
+        pedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<String> {
+        let mut out = String::new();
+        let sections = [
+            self.item(pedia, config, diagnostics)?,
+            self.structure(pedia, diagnostics)?,
+            self.description(pedia, config)?,
+            self.item_recipe(pedia, config, diagnostics)?
+                .map(|recipe| format!("== Recipes ==\n{recipe}")),
+            self.used_in(pedia, config, diagnostics)?
+                .map(|used_in| format!("== Used in ==\n{used_in}")),
+            self.products(pedia, config, diagnostics)?
+                .map(|products| format!("== Products ==\n{products}")),
+            self.slots(pedia, enums, config, diagnostics)?
+                .map(|slots| format!("== Slots ==\n{slots}")),
+            self.connections(config)?
+                .map(|connections| format!("== Connections ==\n{connections}")),
+            self.data_network_properties(pedia, enums, config, diagnostics)?,
+            self.memory(diagnostics)?
+                .map(|memory| format!("== Memory ==\n{memory}")),
+        ];
+        for section in sections.into_iter().flatten() {
+            writeln!(out, "\n{section}")?;
+        }
+        Ok(out)
+    }
 }
 
 impl Wikibox {
+    /// Either the full article or the loose fragments for `page`, depending on `--page`.
+    fn generate(
+        &self,
+        page: &Page,
+        pedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<String> {
+        if self.page {
+            page.article(pedia, enums, config, diagnostics)
+        } else {
+            page.wikibox(pedia, enums, config, diagnostics)
+        }
+    }
+
     pub(crate) fn run(
         &self,
         stationpedia: &crate::stationpedia::Stationpedia,
         enums: &crate::enums::Enums,
-        config: &toml_edit::DocumentMut,
+        config: &Config,
         verbose: bool,
     ) -> color_eyre::Result<()> {
+        if self.all {
+            return self.run_all(stationpedia, enums, config, verbose);
+        }
+        let item = self
+            .item
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("no item given"))?;
         // Pair up items that construct things with that thing.
         let mut hits = vec![];
-        let page = if let Ok(hash) = self.item.parse::<i64>() {
-            stationpedia
-                .pages
-                .iter()
-                .find(|p| p.prefab_hash == hash)
-                .unwrap()
+        let page = if let Ok(hash) = item.parse::<i64>() {
+            let Some(page) = stationpedia.lookup_hash(hash) else {
+                eprintln!("No page found with prefab hash {hash}");
+                return Ok(());
+            };
+            page
         } else {
             // find matches, either by key, title or prefab_name
             let mut matcher = nucleo_matcher::Matcher::default();
 
             let mut pat = None::<nucleo_matcher::pattern::Pattern>;
             for page in &stationpedia.pages {
-                if text_match(&mut matcher, &mut pat, &page.key, &self.item) > 100
-                    || text_match(&mut matcher, &mut pat, &page.title, &self.item) > 100
-                    || text_match(&mut matcher, &mut pat, &page.prefab_name, &self.item) > 100
+                if text_match(&mut matcher, &mut pat, &page.key, item) > 100
+                    || text_match(&mut matcher, &mut pat, &page.title, item) > 100
+                    || text_match(&mut matcher, &mut pat, &page.prefab_name, item) > 100
                 {
                     hits.push(page);
                 }
             }
             if hits.is_empty() {
-                eprintln!("No matches found for {}", self.item);
+                eprintln!("No matches found for {}", item);
                 return Ok(());
             }
             if let Some(exact) = hits
                 .iter()
-                .find(|p| p.key == self.item || p.title == self.item || p.prefab_name == self.item)
+                .find(|p| p.key == item || p.title == item || p.prefab_name == item)
             {
                 exact
             } else {
                 if hits.len() > 1 {
-                    eprintln!("Multiple matches found for {}", self.item);
+                    eprintln!("Multiple matches found for {}", item);
                     for m in hits {
                         eprintln!("  {} - {}", m.key, m.title);
                     }
@@ -667,28 +806,120 @@ impl Wikibox {
             eprintln!("got match: \n---page:\n{:#?}", page,);
         }
 
-        if let Some(item) = page.item(stationpedia)? {
-            println!("\n{}", item);
-        }
+        let mut diagnostics = Diagnostics::default();
+        print!(
+            "{}{}",
+            super::provenance(stationpedia),
+            self.generate(page, stationpedia, enums, config, &mut diagnostics)?
+        );
+        diagnostics.report();
 
-        if let Some(structure) = page.structure(stationpedia)? {
-            println!("\n{}", structure);
-        }
+        Ok(())
+    }
 
-        if let Some(description) = page.description(stationpedia, config)? {
-            println!("\n{}", description);
+    /// Generate every page matching the filter into `out_dir`, one file per page.
+    ///
+    /// Failing pages are collected and reported at the end instead of aborting the run, which then fails.
+    fn run_all(
+        &self,
+        stationpedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        verbose: bool,
+    ) -> color_eyre::Result<()> {
+        let out_dir = self
+            .out_dir
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("--all requires --out-dir"))?;
+        std::fs::create_dir_all(out_dir)?;
+
+        let mut written = 0;
+        let mut empty = vec![];
+        let mut errored = vec![];
+        let mut skipped = vec![];
+        let mut renamed = vec![];
+        let mut seen_titles = std::collections::HashSet::new();
+        let mut diagnostics = Diagnostics::default();
+        for page in &stationpedia.pages {
+            if let Some(filter) = &self.filter {
+                if !(filter.is_match(&page.key)
+                    || filter.is_match(&page.title)
+                    || filter.is_match(&page.prefab_name))
+                {
+                    continue;
+                }
+            }
+            if page.title.starts_with("<N:") {
+                skipped.push((page, "untranslated title"));
+                continue;
+            }
+            let out = match self.generate(page, stationpedia, enums, config, &mut diagnostics) {
+                Ok(out) => out,
+                Err(e) => {
+                    errored.push((page, e));
+                    continue;
+                }
+            };
+            if out.trim().is_empty() {
+                empty.push(page);
+                continue;
+            }
+            // pages sharing a title, like a kit and the structure it builds, are told apart by prefab name
+            let name = if seen_titles.insert(page.title.as_str()) {
+                page.title.clone()
+            } else {
+                format!("{} ({})", page.title, page.prefab_name)
+            };
+            let path = out_dir.join(format!("{}.wiki", name.replace('/', "%2F")));
+            if verbose {
+                eprintln!("writing {}", path.display());
+            }
+            if let Err(e) = std::fs::write(&path, super::provenance(stationpedia) + &out)
+                .wrap_err_with(|| format!("couldn't write {}", path.display()))
+            {
+                errored.push((page, e));
+                continue;
+            }
+            if name != page.title {
+                renamed.push((page, path));
+            }
+            written += 1;
         }
 
-        if let Some(item) = page.item_recipe(stationpedia)? {
-            println!("\n{}", item);
+        eprintln!("Wrote {written} pages to {}", out_dir.display());
+        if !empty.is_empty() {
+            eprintln!("{} pages produced no output:", empty.len());
+            for page in &empty {
+                eprintln!("  {} - {}", page.prefab_name, page.title);
+            }
         }
-
-        if let Some(data_network_properties) =
-            page.data_network_properties(stationpedia, enums, config)?
-        {
-            println!("\n{}", data_network_properties);
+        if !skipped.is_empty() {
+            eprintln!("{} pages skipped:", skipped.len());
+            for (page, reason) in &skipped {
+                eprintln!("  {} - {}: {reason}", page.prefab_name, page.title);
+            }
+        }
+        if !renamed.is_empty() {
+            eprintln!(
+                "{} pages share their title with another page, publish them with --title:",
+                renamed.len()
+            );
+            for (page, path) in &renamed {
+                eprintln!("  {} - {}: {}", page.prefab_name, page.title, path.display());
+            }
+        }
+        diagnostics.report();
+        if !errored.is_empty() {
+            eprintln!("{} pages errored:", errored.len());
+            for (page, e) in &errored {
+                eprintln!("  {} - {}: {e:#}", page.prefab_name, page.title);
+            }
+            eyre::bail!(
+                "failed {} of {} pages",
+                errored.len(),
+                written + empty.len() + errored.len()
+            );
         }
-
         Ok(())
     }
 }
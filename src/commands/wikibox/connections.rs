@@ -0,0 +1,63 @@
+//! Connections of a page, from `Device.ConnectionList` and `ConnectionInsert`.
+
+use crate::{config::Config, stationpedia::Page, wikitext::Template};
+
+use super::with_rows;
+
+/// Friendly name of a connection type or role from `connections.names`, linked via `connections.links`.
+fn connection_name(config: &Config, name: &str, link: bool) -> String {
+    let friendly = config
+        .connections
+        .names
+        .get(name)
+        .map_or(name, String::as_str);
+    let target = config.connections.links.get(name).map(String::as_str);
+    match target {
+        Some(target) if link && target == friendly => format!("[[{target}]]"),
+        Some(target) if link => format!("[[{target}|{friendly}]]"),
+        _ => friendly.to_owned(),
+    }
+}
+
+impl Page {
+    pub fn connections(&self, config: &Config) -> color_eyre::Result<Option<String>> {
+        // devices know the type and role of each connection, for everything else we only have the display name
+        let connections = match self.device.as_ref().map(|d| &d.connection_list) {
+            Some(list) if !list.is_empty() => list
+                .iter()
+                .map(|c| {
+                    let ty = c.first().map(String::as_str).unwrap_or_default();
+                    let role = c.get(1).map(String::as_str).filter(|r| *r != "None");
+                    (
+                        connection_name(config, ty, true),
+                        role.map(|r| connection_name(config, r, false)),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            _ if self
+                .connection_insert
+                .iter()
+                .any(|c| c.logic_name != "Connection") =>
+            {
+                self.connection_insert
+                    .iter()
+                    .map(|c| (c.logic_name.clone(), None))
+                    .collect()
+            }
+            _ => return Ok(None),
+        };
+
+        let rows = connections
+            .iter()
+            .enumerate()
+            .map(|(index, (ty, role))| {
+                let mut row = Template::new("Connections/row");
+                row.positional(index)
+                    .positional(ty)
+                    .positional(role.as_deref().unwrap_or_default());
+                row
+            })
+            .collect::<Vec<_>>();
+        Ok(Some(with_rows("Connections", &rows)))
+    }
+}
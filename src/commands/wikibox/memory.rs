@@ -0,0 +1,56 @@
+//! Memory of a page, from `Memory` and the bit layouts of its instructions.
+
+use std::fmt::Write as _;
+
+use crate::{
+    diagnostics::{Diagnostics, Missing, Section},
+    stationpedia::Page,
+    wikitext::{Layout, Template},
+};
+
+impl Page {
+    pub fn memory(&self, diagnostics: &mut Diagnostics) -> color_eyre::Result<Option<String>> {
+        let Some(memory) = &self.memory else {
+            return Ok(None);
+        };
+        let mut out = Template::new("Memory")
+            .param("access", &memory.memory_access)
+            .param("size", memory.memory_size)
+            .param("size_readable", &memory.memory_size_readable)
+            .render(Layout::Pretty);
+
+        let mut instructions = memory.instructions.iter().flatten().collect::<Vec<_>>();
+        instructions.sort_by_key(|(_, i)| i.value);
+        for (name, instruction) in instructions {
+            write!(
+                out,
+                "\n\n=== {name} ===\n{}, value {}.",
+                instruction.type_, instruction.value
+            )?;
+            let layout = match instruction.layout() {
+                Ok(layout) => layout,
+                Err(e) => {
+                    diagnostics.push(
+                        &self.key,
+                        Section::Memory,
+                        Missing::Unexpected(format!("instruction `{name}`: {e}")),
+                    );
+                    continue;
+                }
+            };
+            for note in &layout.notes {
+                write!(out, " ''{note}''")?;
+            }
+            out.push_str("\n{| class=\"wikitable\"\n! Bits !! Field !! Type");
+            for field in &layout.fields {
+                write!(
+                    out,
+                    "\n|-\n| {}-{} || {} || {}",
+                    field.start, field.end, field.name, field.ty
+                )?;
+            }
+            out.push_str("\n|}");
+        }
+        Ok(Some(out))
+    }
+}
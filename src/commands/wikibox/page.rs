@@ -0,0 +1,126 @@
+//! Assembles the wikibox sections into a complete article, following the `[page]` skeleton in the config.
+
+use std::fmt::Write as _;
+
+use color_eyre::eyre;
+
+use crate::{
+    config::{Config, When},
+    diagnostics::Diagnostics,
+    enums::Enums,
+    stationpedia::{Page, Stationpedia},
+};
+
+impl Page {
+    /// The generated content for a section named in the page skeleton.
+    pub fn section(
+        &self,
+        name: &str,
+        pedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
+        Ok(match name {
+            "infobox" => {
+                let boxes = [
+                    self.item(pedia, config, diagnostics)?,
+                    self.structure(pedia, diagnostics)?,
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+                (!boxes.is_empty()).then(|| boxes.join("\n\n"))
+            }
+            "item" => self.item(pedia, config, diagnostics)?,
+            "structure" => self.structure(pedia, diagnostics)?,
+            "description" => self.description(pedia, config)?,
+            "recipes" => self.item_recipe(pedia, config, diagnostics)?,
+            "used_in" => self.used_in(pedia, config, diagnostics)?,
+            "products" => self.products(pedia, config, diagnostics)?,
+            "slots" => self.slots(pedia, enums, config, diagnostics)?,
+            "connections" => self.connections(config)?,
+            "memory" => self.memory(diagnostics)?,
+            "data_network" => self.data_network_properties(pedia, enums, config, diagnostics)?,
+            _ => eyre::bail!("unknown section `{name}` in page skeleton"),
+        })
+    }
+
+    /// Whether a skeleton entry with `when = "..."` applies to this page.
+    fn applies(&self, when: Option<When>) -> bool {
+        match when {
+            None => true,
+            Some(When::Item) => self.item.is_some(),
+            Some(When::Structure) => self.structure.is_some(),
+            Some(When::Device) => self.device.is_some(),
+            Some(When::Logic) => self.logic_info.is_some(),
+        }
+    }
+
+    /// A complete article for this page.
+    ///
+    /// Entries in `page.sections` are emitted in order, each either a generated `section` or a literal `text`,
+    /// optionally under a `heading`. Sections without content for this page are left out along with their heading.
+    /// Entries in `page.footer` are added at the end, one per line.
+    pub fn article(
+        &self,
+        pedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<String> {
+        let skeleton = &config.page;
+        if skeleton.sections.is_empty() {
+            eyre::bail!("no [page] skeleton in config");
+        }
+
+        // placeholders available in literal text
+        let fill = |text: &str| {
+            textwrap::dedent(text)
+                .trim()
+                .replace("{title}", &self.title)
+                .replace("{prefab_name}", &self.prefab_name)
+        };
+        let mut parts = vec![];
+        for entry in &skeleton.sections {
+            if !self.applies(entry.when) {
+                continue;
+            }
+            let content = if let Some(section) = &entry.section {
+                self.section(section, pedia, enums, config, diagnostics)?
+            } else if let Some(text) = &entry.text {
+                Some(fill(text))
+            } else {
+                eyre::bail!("page sections need either a `section` or a `text`");
+            };
+            let Some(content) = content else {
+                continue;
+            };
+            let mut part = String::new();
+            if let Some(heading) = &entry.heading {
+                let level = entry.level.unwrap_or(2).clamp(1, 6) as usize;
+                let eq = "=".repeat(level);
+                writeln!(part, "{eq} {} {eq}", fill(heading))?;
+            }
+            part.push_str(content.trim_end());
+            parts.push(part);
+        }
+
+        let mut footer = vec![];
+        for entry in &skeleton.footer {
+            if !self.applies(entry.when) {
+                continue;
+            }
+            if let Some(text) = &entry.text {
+                footer.push(fill(text));
+            }
+        }
+        if !footer.is_empty() {
+            parts.push(footer.join("\n"));
+        }
+
+        let mut out = parts.join("\n\n");
+        out.push('\n');
+        Ok(out)
+    }
+}
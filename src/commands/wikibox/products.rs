@@ -0,0 +1,107 @@
+//! Everything a fabricator can make, from `Device.Fabricator.Recipes`.
+
+use std::fmt::Write as _;
+
+use crate::{
+    config::Config,
+    diagnostics::{Diagnostics, Section},
+    stationpedia::{Page, Recipe, Stationpedia},
+};
+
+use super::recipe_ingredients;
+
+/// Bounds at or above this are the game's way of saying "no upper limit".
+const UNBOUNDED: f64 = 99999.0;
+
+/// `TierOne` -> `Tier One`
+fn tier_name(tier: &str) -> String {
+    match tier.strip_prefix("Tier") {
+        Some(n) if !n.is_empty() => format!("Tier {n}"),
+        _ => tier.to_owned(),
+    }
+}
+
+fn range(start: f64, stop: f64, unit: &str) -> String {
+    if stop >= UNBOUNDED {
+        format!("≥ {start} {unit}")
+    } else {
+        format!("{start}–{stop} {unit}")
+    }
+}
+
+/// Temperature, pressure and gas requirements of a recipe, if any.
+fn recipe_conditions(recipe: &Recipe) -> Vec<String> {
+    let mut conditions = vec![];
+    if recipe.temperature.is_valid {
+        conditions.push(format!(
+            "Temperature {}",
+            range(recipe.temperature.start, recipe.temperature.stop, "K")
+        ));
+    }
+    if recipe.pressure.is_valid {
+        conditions.push(format!(
+            "Pressure {}",
+            range(recipe.pressure.start, recipe.pressure.stop, "kPa")
+        ));
+    }
+    let gases = recipe
+        .required_mix
+        .reagents
+        .iter()
+        .filter(|(_, q)| *q > &0.0)
+        .map(|(gas, _)| format!("[[{gas}]]"))
+        .collect::<Vec<_>>();
+    if !gases.is_empty() {
+        let join = if recipe.required_mix.is_any {
+            " or "
+        } else {
+            " and "
+        };
+        conditions.push(format!("Requires {}", gases.join(join)));
+    }
+    conditions
+}
+
+impl Page {
+    pub fn products(
+        &self,
+        pedia: &Stationpedia,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
+        let Some(fabricator) = self.device.as_ref().and_then(|d| d.fabricator.as_ref()) else {
+            return Ok(None);
+        };
+        if fabricator.recipes.is_empty() {
+            return Ok(None);
+        }
+        // only add the conditions column if any recipe has conditions
+        let has_conditions = fabricator
+            .recipes
+            .values()
+            .any(|r| !recipe_conditions(r).is_empty());
+
+        let mut out = String::new();
+        out.push_str("{| class=\"wikitable sortable\"\n");
+        out.push_str("! Product !! Tier !! Time (s) !! Energy (J) !! Ingredients");
+        if has_conditions {
+            out.push_str(" !! Conditions");
+        }
+        for (prefab_name, recipe) in &fabricator.recipes {
+            let product = self.title_of(pedia, prefab_name, Section::Recipe, diagnostics);
+            write!(
+                out,
+                "\n|-\n| [[{product}]] || {} || {} || {} || {}",
+                tier_name(&recipe.tier_name),
+                recipe.time,
+                recipe.energy,
+                recipe_ingredients(pedia, config, recipe)?
+            )?;
+            if has_conditions {
+                write!(out, " || {}", recipe_conditions(recipe).join("<br>"))?;
+            }
+        }
+        out.push_str("\n|}");
+        Ok(Some(out))
+    }
+}
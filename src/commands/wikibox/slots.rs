@@ -0,0 +1,103 @@
+//! Slot layout of a page, from `SlotInserts` and `LogicInfo.LogicSlotTypes`.
+
+use crate::{
+    config::Config,
+    diagnostics::{Diagnostics, Missing, Section},
+    enums::Enums,
+    stationpedia::{Page, Stationpedia},
+    wikitext::Template,
+};
+
+use super::{logic_replacements, wikify, with_rows};
+
+impl Page {
+    pub fn slots(
+        &self,
+        pedia: &Stationpedia,
+        enums: &Enums,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
+        if self.slot_inserts.is_empty() {
+            return Ok(None);
+        }
+        let logic_slot_types = self.logic_info.as_ref().map(|l| &l.logic_slot_types);
+        let slot_type_enum = enums.script_enums.get("LogicSlotType");
+
+        // every slot type used by any slot, in order of first appearance
+        let mut used = indexmap::IndexSet::new();
+        let mut rows = vec![];
+        for slot in &self.slot_inserts {
+            let mut read = vec![];
+            let mut write = vec![];
+            for (slot_type, rw) in logic_slot_types
+                .and_then(|l| l.get(&slot.slot_index))
+                .into_iter()
+                .flat_map(|l| &l.slot_types)
+            {
+                if slot_type_enum
+                    .and_then(|e| e.values.get(slot_type))
+                    .is_some_and(|e| e.deprecated)
+                {
+                    continue;
+                }
+                if rw.contains("Read") {
+                    read.push(slot_type.as_str());
+                }
+                if rw.contains("Write") {
+                    write.push(slot_type.as_str());
+                }
+                used.insert(slot_type.as_str());
+            }
+            let mut row = Template::new("Slots/row");
+            row.param("index", &slot.slot_index)
+                .param("name", &slot.slot_name)
+                .param("class", &slot.slot_type)
+                .param("read", read.join(", "))
+                .param("write", write.join(", "));
+            rows.push(row);
+        }
+        let mut out = with_rows("Slots", &rows);
+
+        if !used.is_empty() {
+            let replacements = logic_replacements(config, &self.prefab_name)?;
+            let mut rows = vec![];
+            for slot_type in used {
+                let mut row = Template::new("Slot Parameters/row");
+                row.positional(slot_type);
+                let conf_global = config.logic.slot_types.get(slot_type);
+                let conf_device = config
+                    .logic
+                    .device
+                    .get(self.prefab_name.as_str())
+                    .and_then(|d| d.slot_types.get(slot_type));
+                if let Some(desc) = conf_device
+                    .or(conf_global)
+                    .and_then(|i| i.description.as_deref())
+                {
+                    row.positional(wikify(desc, pedia, config)?);
+                } else if let Some(entry) = slot_type_enum.and_then(|e| e.values.get(slot_type)) {
+                    let mut desc = entry.description.clone();
+                    for replace in &replacements {
+                        desc = replace.0.replace_all(&desc, replace.1).to_string();
+                    }
+                    row.positional(wikify(&desc, pedia, config)?);
+                } else {
+                    diagnostics.push(
+                        &self.key,
+                        Section::Logic,
+                        Missing::Enum {
+                            listing: "LogicSlotType".to_owned(),
+                            name: slot_type.to_owned(),
+                        },
+                    );
+                }
+                rows.push(row);
+            }
+            out.push('\n');
+            out.push_str(&with_rows("Slot Parameters", &rows));
+        }
+
+        Ok(Some(out.replace("{device}", &self.title)))
+    }
+}
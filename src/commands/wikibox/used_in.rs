@@ -0,0 +1,78 @@
+//! Recipes consuming a page's item, from the reverse recipe index.
+
+use std::fmt::Write as _;
+
+use crate::{
+    config::Config,
+    diagnostics::{Diagnostics, Missing, Section},
+    stationpedia::{Page, RecipeUse, Stationpedia},
+};
+
+use super::recipe_amount;
+
+impl Page {
+    pub fn used_in(
+        &self,
+        pedia: &Stationpedia,
+        config: &Config,
+        diagnostics: &mut Diagnostics,
+    ) -> color_eyre::Result<Option<String>> {
+        used_in_table(
+            pedia,
+            config,
+            &self.key,
+            pedia.recipes_using_prefab(&self.prefab_name),
+            diagnostics,
+        )
+    }
+}
+
+/// A sortable table of recipes, given as the consumed reagent and the recipe.
+///
+/// `page` is the page the table is for, used in diagnostics.
+pub(crate) fn used_in_table<'a>(
+    pedia: &'a Stationpedia,
+    config: &Config,
+    page: &str,
+    uses: impl IntoIterator<Item = (&'a str, &'a RecipeUse)>,
+    diagnostics: &mut Diagnostics,
+) -> color_eyre::Result<Option<String>> {
+    let mut title = |prefab_name: &'a str| match pedia.lookup_prefab_name(prefab_name) {
+        Some(page) => page.title.as_str(),
+        None => {
+            diagnostics.push(
+                page,
+                Section::Recipe,
+                Missing::PrefabName(prefab_name.to_owned()),
+            );
+            prefab_name
+        }
+    };
+    let mut rows = vec![];
+    for (reagent, used) in uses {
+        let recipe = &used.recipe;
+        let product = title(&used.product);
+        let machine = title(&recipe.creator_prefab_name);
+        let (amount, _) = recipe_amount(pedia, config, reagent, &recipe.creator_prefab_name);
+        let quantity = recipe.reagents.get(reagent).copied().unwrap_or_default();
+        rows.push((
+            product.to_owned(),
+            format!("{quantity}{amount}"),
+            machine.to_owned(),
+        ));
+    }
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    // sort by what readers see, the prefab names don't always match the titles
+    rows.sort();
+
+    let mut out = String::new();
+    out.push_str("{| class=\"wikitable sortable\"\n");
+    out.push_str("! Product !! Quantity !! Machine");
+    for (product, quantity, machine) in rows {
+        write!(out, "\n|-\n| [[{product}]] || {quantity} || [[{machine}]]")?;
+    }
+    out.push_str("\n|}");
+    Ok(Some(out))
+}
@@ -0,0 +1,395 @@
+//! The typed `config.toml`.
+//!
+//! The whole file is deserialized up front, so unknown keys and values of the wrong shape are reported with their
+//! location instead of being silently ignored halfway through a run. Keys that name things in the dumps, like
+//! prefab names and logic types, keep their [`Spanned`] location so `config check` can point at them too.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::WrapErr;
+use indexmap::IndexMap;
+use serde::de;
+use serde_derive::Deserialize;
+pub use serde_spanned::Spanned;
+
+use crate::enums::ValueType;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Categories of IC10 instructions, with examples and overrides, for `instructions`
+    pub instructions: InstructionCategory,
+    pub logic: Logic,
+    pub bot: Bot,
+    pub stationpedia: StationpediaConfig,
+    pub reagents: Reagents,
+    pub character: CharacterConfig,
+    /// Per enum settings for `enums`, keyed by enum name
+    pub enums: BTreeMap<Spanned<String>, EnumConfig>,
+    pub page: PageConfig,
+    pub connections: Connections,
+    /// Where the config was loaded from, and its text, to turn spans into locations
+    #[serde(skip)]
+    source: Option<(PathBuf, String)>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> color_eyre::Result<Config> {
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        let mut config: Config = toml_edit::de::from_str(&text)
+            .wrap_err_with(|| format!("invalid config {}", path.display()))?;
+        config.source = Some((path.to_owned(), text));
+        Ok(config)
+    }
+
+    /// `path:line:column` of a span in the config file.
+    pub fn location(&self, span: Range<usize>) -> String {
+        let Some((path, text)) = &self.source else {
+            return format!("config bytes {}..{}", span.start, span.end);
+        };
+        let before = &text[..span.start.min(text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        format!("{}:{line}:{column}", path.display())
+    }
+
+    /// The wiki page `stationpedia.links` sends a `<link=...>` target to.
+    pub fn link(&self, target: &str) -> Option<&str> {
+        self.stationpedia.links.get(target).map(String::as_str)
+    }
+
+    /// Overrides for a logic type on a device, if any.
+    pub fn device_logic_type(
+        &self,
+        prefab_name: &str,
+        logic_type: &str,
+    ) -> Option<&LogicTypeConfig> {
+        self.logic.device.get(prefab_name)?.types.get(logic_type)
+    }
+}
+
+/// A category of the `instructions` page, with its instructions and subcategories in the order of the file.
+#[derive(Debug, Default)]
+pub struct InstructionCategory {
+    pub instructions: Vec<Spanned<InstructionConfig>>,
+    pub categories: IndexMap<String, InstructionCategory>,
+}
+
+impl InstructionCategory {
+    /// Every category below this one, depth first, as its path of names.
+    pub fn walk(&self) -> Vec<(Vec<&str>, &InstructionCategory)> {
+        let mut out = vec![];
+        for (name, category) in &self.categories {
+            out.push((vec![name.as_str()], category));
+            for (mut path, sub) in category.walk() {
+                path.insert(0, name.as_str());
+                out.push((path, sub));
+            }
+        }
+        out
+    }
+
+    /// The first category listing `op`, with the path to it and the position of the instruction in it.
+    pub fn find(&self, op: &str) -> Option<(Vec<&str>, usize, &InstructionConfig)> {
+        self.walk().into_iter().find_map(|(path, category)| {
+            category
+                .instructions
+                .iter()
+                .enumerate()
+                .find(|(_, i)| i.get_ref().op == op)
+                .map(|(order, i)| (path, order, i.get_ref()))
+        })
+    }
+}
+
+impl<'de> de::Deserialize<'de> for InstructionCategory {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = InstructionCategory;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a table of `instructions` and subcategories")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut category = InstructionCategory::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "instructions" {
+                        category.instructions = map.next_value::<Instructions>()?.0;
+                    } else {
+                        let sub = map.next_value()?;
+                        category.categories.insert(key, sub);
+                    }
+                }
+                Ok(category)
+            }
+        }
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+/// An instruction in a category, written either as just its name or as a table with `op` and overrides.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstructionConfig {
+    pub op: String,
+    /// Example put below the instruction, usually an `{{ICCode}}` block
+    pub example: Option<String>,
+    pub note: Option<String>,
+    /// Replaces the description from the stationpedia
+    pub desc: Option<String>,
+    /// Replaces the syntax from the stationpedia
+    pub syntax: Option<String>,
+}
+
+impl<'de> de::Deserialize<'de> for OpOrTable {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = OpOrTable;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an instruction name or a table with `op`")
+            }
+
+            fn visit_str<E: de::Error>(self, op: &str) -> Result<Self::Value, E> {
+                Ok(OpOrTable(InstructionConfig {
+                    op: op.to_owned(),
+                    ..Default::default()
+                }))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                de::Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(OpOrTable)
+            }
+        }
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Deserializes an [`InstructionConfig`] from either of its forms.
+struct OpOrTable(InstructionConfig);
+
+/// The `instructions` list of a category.
+struct Instructions(Vec<Spanned<InstructionConfig>>);
+
+impl<'de> de::Deserialize<'de> for Instructions {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let instructions = Vec::<Spanned<OpOrTable>>::deserialize(deserializer)?;
+        Ok(Instructions(
+            instructions
+                .into_iter()
+                .map(|i| Spanned::new(i.span(), i.into_inner().0))
+                .collect(),
+        ))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Logic {
+    /// Overrides for logic types on every device
+    pub types: BTreeMap<Spanned<String>, LogicTypeConfig>,
+    /// Overrides for logic slot types on every device
+    pub slot_types: BTreeMap<Spanned<String>, SlotTypeConfig>,
+    /// Overrides for a single device, keyed by prefab name
+    pub device: BTreeMap<Spanned<String>, DeviceLogic>,
+    /// Description replacements for every device
+    pub replace: Vec<Replace>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogicTypeConfig {
+    #[serde(rename = "type")]
+    pub ty: Option<ValueType>,
+    pub unit: Option<String>,
+    pub range: Option<String>,
+    /// Replaces the description from the enums, `{device}` is replaced with the page title
+    pub description: Option<String>,
+    pub values: Option<Values>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlotTypeConfig {
+    pub description: Option<String>,
+}
+
+/// The possible values of a logic type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Values {
+    /// Names of the values 0, 1, 2, ...
+    List(Vec<String>),
+    /// Names by value, like `{ "-1" = "Unset", "0" = "Straight" }`
+    Map(IndexMap<String, String>),
+    /// Free text, like a range `0.0 to 10.0`
+    Text(String),
+}
+
+impl<'de> de::Deserialize<'de> for Values {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Values;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of names, a table of value = name, or a string")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                Ok(Values::Text(text.to_owned()))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                de::Deserialize::deserialize(de::value::SeqAccessDeserializer::new(seq))
+                    .map(Values::List)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                de::Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(Values::Map)
+            }
+        }
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Overrides for one device: its logic types by name, plus `replace` and `slot_types`.
+#[derive(Debug, Default)]
+pub struct DeviceLogic {
+    pub types: BTreeMap<Spanned<String>, LogicTypeConfig>,
+    pub slot_types: BTreeMap<Spanned<String>, SlotTypeConfig>,
+    /// Description replacements, applied before the ones in `logic.replace`
+    pub replace: Vec<Replace>,
+}
+
+impl<'de> de::Deserialize<'de> for DeviceLogic {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = DeviceLogic;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a table of logic types, `replace` and `slot_types`")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut device = DeviceLogic::default();
+                while let Some(key) = map.next_key::<Spanned<String>>()? {
+                    match key.get_ref().as_str() {
+                        "replace" => device.replace = map.next_value()?,
+                        "slot_types" => device.slot_types = map.next_value()?,
+                        _ => {
+                            let value = map.next_value()?;
+                            device.types.insert(key, value);
+                        }
+                    }
+                }
+                Ok(device)
+            }
+        }
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+/// A regex replacement for logic descriptions, lower `prio` is applied first.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Replace {
+    pub regex: Spanned<String>,
+    pub replace: String,
+    #[serde(default)]
+    pub prio: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bot {
+    pub api_url: Option<String>,
+    pub rest_url: Option<String>,
+    /// Bot password login, `WIKI_DUMPER_USERNAME` in the environment takes precedence
+    pub username: Option<String>,
+    /// Bot password, `WIKI_DUMPER_PASSWORD` in the environment takes precedence
+    pub password: Option<String>,
+    /// Edit summary used when `--summary` isn't given
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StationpediaConfig {
+    /// Wiki pages for `<link=...>` targets in descriptions
+    pub links: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Reagents {
+    /// Wiki page names by reagent
+    pub names: BTreeMap<String, String>,
+    /// Units for recipe amounts by wiki page name
+    pub units: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CharacterConfig {
+    /// Wiki page names by core prefab name
+    pub names: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnumConfig {
+    /// Text put before the table
+    pub intro: Option<String>,
+}
+
+/// The skeleton of complete articles, see [`crate::stationpedia::Page::article`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PageConfig {
+    pub sections: Vec<PageEntry>,
+    pub footer: Vec<PageEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PageEntry {
+    /// A generated section, like `slots`
+    pub section: Option<String>,
+    /// Literal text
+    pub text: Option<String>,
+    pub heading: Option<String>,
+    /// Heading level, 2 if not given
+    pub level: Option<u8>,
+    pub when: Option<When>,
+}
+
+/// Kinds of pages a [`PageEntry`] can be limited to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum When {
+    Item,
+    Structure,
+    Device,
+    Logic,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Connections {
+    /// Friendly names of connection types and roles
+    pub names: BTreeMap<String, String>,
+    /// Pages to link connection types to
+    pub links: BTreeMap<String, String>,
+}
@@ -0,0 +1,99 @@
+//! Problems found while generating pages.
+//!
+//! Generation should not stop because of a single odd page, so instead of panicking we emit a placeholder
+//! and record a [`Diagnostic`] which is reported when the run is done.
+
+use std::fmt;
+
+/// The part of a page a [`Diagnostic`] was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Section {
+    Structure,
+    Item,
+    Recipe,
+    Logic,
+    Memory,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Section::Structure => "structure",
+            Section::Item => "item",
+            Section::Recipe => "recipe",
+            Section::Logic => "logic",
+            Section::Memory => "memory",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Missing {
+    /// No page with this prefab name
+    PrefabName(String),
+    /// No page with this key
+    Key(String),
+    /// No entry in `Enums.json`
+    Enum { listing: String, name: String },
+    /// The data didn't have the expected shape
+    Unexpected(String),
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Missing::PrefabName(name) => write!(f, "no page with prefab name `{name}`"),
+            Missing::Key(key) => write!(f, "no page with key `{key}`"),
+            Missing::Enum { listing, name } => write!(f, "no `{listing}.{name}` in enums"),
+            Missing::Unexpected(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Key of the page being generated
+    pub page: String,
+    pub section: Section,
+    pub missing: Missing,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]: {}", self.page, self.section, self.missing)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn push(&mut self, page: &str, section: Section, missing: Missing) {
+        let diagnostic = Diagnostic {
+            page: page.to_owned(),
+            section,
+            missing,
+        };
+        tracing::debug!("{diagnostic}");
+        self.0.push(diagnostic);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter()
+    }
+
+    /// Print all diagnostics to stderr.
+    pub fn report(&self) {
+        if self.is_empty() {
+            return;
+        }
+        eprintln!("{} warnings:", self.0.len());
+        for diagnostic in &self.0 {
+            eprintln!("  {diagnostic}");
+        }
+    }
+}
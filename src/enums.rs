@@ -2,9 +2,18 @@ use std::collections::BTreeMap;
 
 use serde_derive::{Deserialize, Serialize};
 
+use crate::stationpedia::GameVersion;
+
+mod logic_types;
+
+pub use logic_types::{LogicTypeInfo, LogicTypes, Source, ValueType};
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename = "Enums")]
 pub struct Enums {
+    /// Version of the game the enums were extracted from, older extractors don't write this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
     #[serde(rename = "scriptEnums")]
     pub script_enums: BTreeMap<String, EnumListing>,
     #[serde(rename = "basicEnums")]
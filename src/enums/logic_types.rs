@@ -0,0 +1,387 @@
+//! What the value of each `LogicType` means: its type, unit, range and named values.
+//!
+//! Resolved from the [`BUILT_IN`] table, or gas ratios from the `GasType` enum, falling back to guessing from the name.
+//! [`LogicTypes::fallbacks`] lists the guessed ones so they can be added to the table. `[logic.types]` in the config is
+//! applied on top of that, and `[logic.device]` on top of both, each only changing the fields it sets.
+
+use std::{collections::BTreeMap, fmt};
+
+use color_eyre::eyre;
+
+use serde_derive::Deserialize;
+
+use super::Enums;
+use crate::config::Config;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub enum ValueType {
+    Boolean,
+    Integer,
+    Float,
+}
+
+impl ValueType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::Boolean => "Boolean",
+            ValueType::Integer => "Integer",
+            ValueType::Float => "Float",
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where the type of a [`LogicTypeInfo`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Source {
+    /// `[logic.types]` in the config
+    Config,
+    /// The [`BUILT_IN`] table
+    BuiltIn,
+    /// A `Ratio<Gas>` type, with the gas from the `GasType` enum
+    GasRatio,
+    /// Guessed from the name
+    Heuristic,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Config => "config",
+            Source::BuiltIn => "built-in",
+            Source::GasRatio => "gas ratio",
+            Source::Heuristic => "heuristic",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogicTypeInfo {
+    pub ty: ValueType,
+    /// Unit of the value, like `K` or `kPa`
+    pub unit: Option<String>,
+    /// Human readable range, like `0.0 to 1.0`
+    pub range: Option<String>,
+    /// Named values, from the enum the value is a member of
+    pub values: Vec<(i64, String)>,
+    pub source: Source,
+}
+
+use ValueType::{Boolean, Float, Integer};
+
+/// `(name, type, unit, range)`
+type Entry = (
+    &'static str,
+    ValueType,
+    Option<&'static str>,
+    Option<&'static str>,
+);
+
+/// Logic types whose type can't be told from [`Enums`].
+///
+/// Network variants like `TemperatureInput2` use the entry without the suffix.
+pub const BUILT_IN: &[Entry] = &[
+    ("Activate", Boolean, None, None),
+    ("AirRelease", Boolean, None, None),
+    ("AutoLand", Boolean, None, None),
+    ("AutoShutOff", Boolean, None, None),
+    ("Combustion", Boolean, None, None),
+    ("Error", Boolean, None, None),
+    ("Filtration", Boolean, None, None),
+    ("Flush", Boolean, None, None),
+    ("ForceWrite", Boolean, None, None),
+    ("Harvest", Boolean, None, None),
+    ("Idle", Boolean, None, None),
+    ("Lock", Boolean, None, None),
+    ("On", Boolean, None, None),
+    ("Open", Boolean, None, None),
+    ("Plant", Boolean, None, None),
+    ("Power", Boolean, None, None),
+    ("BestContactFilter", Integer, None, None),
+    ("Bpm", Integer, None, None),
+    ("CelestialHash", Integer, None, None),
+    ("CelestialParentHash", Integer, None, None),
+    ("ChartedNavPoints", Integer, None, None),
+    ("ClearMemory", Integer, None, None),
+    ("Color", Integer, None, None),
+    ("ContactTypeId", Integer, None, None),
+    ("CurrentCode", Integer, None, None),
+    ("CurrentResearchPodType", Integer, None, None),
+    ("DestinationCode", Integer, None, None),
+    ("ElevatorLevel", Integer, None, None),
+    ("EntityState", Integer, None, None),
+    ("ExportCount", Integer, None, None),
+    ("FlightControlRule", Integer, None, Some("0 to 4")),
+    ("ImportCount", Integer, None, None),
+    ("Index", Integer, None, None),
+    ("LineNumber", Integer, None, None),
+    ("ManualResearchRequiredPod", Integer, None, None),
+    ("MineablesInQueue", Integer, None, None),
+    ("MineablesInVicinity", Integer, None, None),
+    ("Mode", Integer, None, None),
+    ("NameHash", Integer, None, None),
+    ("NavPoints", Integer, None, None),
+    ("Output", Integer, None, None),
+    ("PrefabHash", Integer, None, None),
+    ("Reagents", Integer, None, None),
+    ("RecipeHash", Integer, None, None),
+    ("ReferenceId", Integer, None, None),
+    ("RequestHash", Integer, None, None),
+    ("SignalID", Integer, None, None),
+    ("Sites", Integer, None, None),
+    ("SizeX", Integer, None, None),
+    ("SizeY", Integer, None, None),
+    ("SizeZ", Integer, None, None),
+    ("SoundAlert", Integer, None, None),
+    ("TargetPadIndex", Integer, None, None),
+    ("Acceleration", Float, Some("m/s²"), None),
+    ("AlignmentError", Float, Some("°"), None),
+    ("Altitude", Float, Some("m"), None),
+    ("Apex", Float, Some("m"), None),
+    ("BurnTimeRemaining", Float, Some("s"), None),
+    ("Channel0", Float, None, None),
+    ("Channel1", Float, None, None),
+    ("Channel2", Float, None, None),
+    ("Channel3", Float, None, None),
+    ("Channel4", Float, None, None),
+    ("Channel5", Float, None, None),
+    ("Channel6", Float, None, None),
+    ("Channel7", Float, None, None),
+    ("Charge", Float, Some("J"), None),
+    ("Chart", Float, None, Some("0.0 to 1.0, -1 if unavailable")),
+    ("CollectableGoods", Float, None, None),
+    ("CombustionLimiter", Float, None, Some("0 to 100")),
+    ("CompletionRatio", Float, None, Some("0.0 to 1.0")),
+    ("Density", Float, None, None),
+    (
+        "Discover",
+        Float,
+        None,
+        Some("0.0 to 1.0, -1 if unavailable"),
+    ),
+    ("DistanceAu", Float, Some("AU"), None),
+    ("DistanceKm", Float, Some("km"), None),
+    ("DrillCondition", Float, None, Some("0.0 to 1.0")),
+    ("DryMass", Float, Some("kg"), None),
+    ("Eccentricity", Float, None, Some("0.0 to 1.0")),
+    ("ElevatorSpeed", Float, None, None),
+    ("EnvironmentEfficiency", Float, None, Some("0.0 to 1.0")),
+    ("ExhaustVelocity", Float, Some("m/s"), None),
+    ("ForwardX", Float, None, Some("-1.0 to 1.0")),
+    ("ForwardY", Float, None, Some("-1.0 to 1.0")),
+    ("ForwardZ", Float, None, Some("-1.0 to 1.0")),
+    ("Fuel", Float, None, None),
+    ("Horizontal", Float, Some("°"), None),
+    ("HorizontalRatio", Float, None, Some("0.0 to 1.0")),
+    ("Inclination", Float, Some("°"), None),
+    ("InterrogationProgress", Float, None, Some("0.0 to 1.0")),
+    ("Mass", Float, Some("kg"), None),
+    ("Maximum", Float, None, None),
+    ("MinedQuantity", Float, None, None),
+    ("MinimumWattsToContact", Float, Some("W"), None),
+    ("NextWeatherEventTime", Float, Some("s"), None),
+    ("OperationalTemperatureEfficiency", Float, None, None),
+    ("OrbitPeriod", Float, Some("days"), None),
+    ("Orientation", Float, Some("°"), None),
+    ("PassedMoles", Float, Some("mol"), None),
+    ("PositionX", Float, Some("m"), None),
+    ("PositionY", Float, Some("m"), None),
+    ("PositionZ", Float, Some("m"), None),
+    ("PowerActual", Float, Some("W"), None),
+    ("PowerGeneration", Float, Some("W"), None),
+    ("PowerPotential", Float, Some("W"), None),
+    ("PowerRequired", Float, Some("W"), None),
+    ("Pressure", Float, Some("kPa"), None),
+    ("PressureEfficiency", Float, None, None),
+    ("PressureExternal", Float, Some("kPa"), None),
+    ("PressureInternal", Float, Some("kPa"), None),
+    ("PressureSetting", Float, Some("kPa"), None),
+    ("Progress", Float, None, Some("0.0 to 1.0")),
+    ("Quantity", Float, None, None),
+    ("Ratio", Float, None, Some("0.0 to 1.0")),
+    ("ReEntryAltitude", Float, Some("km"), Some("25 to 120")),
+    ("RequiredPower", Float, Some("W"), None),
+    ("ReturnFuelCost", Float, None, None),
+    ("Richness", Float, None, None),
+    ("Rpm", Float, Some("rpm"), None),
+    ("SemiMajorAxis", Float, Some("AU"), None),
+    ("Setting", Float, None, None),
+    ("SignalStrength", Float, Some("°"), None),
+    ("Size", Float, None, None),
+    ("SolarAngle", Float, Some("°"), None),
+    ("SolarIrradiance", Float, None, None),
+    ("Stress", Float, None, Some("0 to 100")),
+    ("Survey", Float, None, Some("0.0 to 1.0, -1 if unavailable")),
+    ("TargetX", Float, Some("m"), None),
+    ("TargetY", Float, Some("m"), None),
+    ("TargetZ", Float, Some("m"), None),
+    ("Temperature", Float, Some("K"), None),
+    ("TemperatureDifferentialEfficiency", Float, None, None),
+    ("TemperatureExternal", Float, Some("K"), None),
+    ("TemperatureSetting", Float, Some("K"), None),
+    ("Throttle", Float, None, Some("0 to 100")),
+    ("Thrust", Float, Some("N"), None),
+    ("ThrustToWeight", Float, None, None),
+    ("Time", Float, None, None),
+    ("TimeToDestination", Float, Some("s"), None),
+    ("TotalMoles", Float, Some("mol"), None),
+    ("TotalQuantity", Float, None, None),
+    ("TrueAnomaly", Float, Some("°"), None),
+    ("VelocityMagnitude", Float, Some("m/s"), None),
+    ("VelocityRelativeX", Float, Some("m/s"), None),
+    ("VelocityRelativeY", Float, Some("m/s"), None),
+    ("VelocityRelativeZ", Float, Some("m/s"), None),
+    ("VelocityX", Float, Some("m/s"), None),
+    ("VelocityY", Float, Some("m/s"), None),
+    ("VelocityZ", Float, Some("m/s"), None),
+    ("Vertical", Float, Some("°"), None),
+    ("VerticalRatio", Float, None, Some("0.0 to 1.0")),
+    ("Volume", Float, Some("L"), None),
+    ("VolumeOfLiquid", Float, Some("L"), None),
+    ("WattsReachingContact", Float, Some("W"), None),
+    ("Weight", Float, Some("N"), None),
+    ("WorkingGasEfficiency", Float, None, Some("0.0 to 1.0")),
+];
+
+/// Logic types whose value is a member of a basic enum, as `(logic type, enum key)`.
+pub const ENUM_VALUES: &[(&str, &str)] = &[("Color", "Color"), ("EntityState", "EntityState")];
+
+/// Suffixes of logic types that read the same value from one of the device's networks.
+const NETWORK_SUFFIXES: &[&str] = &["Input2", "Output2", "Input", "Output"];
+
+fn built_in(name: &str) -> Option<&'static Entry> {
+    BUILT_IN.iter().find(|(n, ..)| *n == name).or_else(|| {
+        let base = NETWORK_SUFFIXES.iter().find_map(|s| name.strip_suffix(s))?;
+        BUILT_IN.iter().find(|(n, ..)| *n == base)
+    })
+}
+
+/// The gas of a `Ratio<Gas>` logic type, including network variants like `RatioOxygenInput`.
+fn ratio_gas<'a>(enums: &Enums, name: &'a str) -> Option<&'a str> {
+    let gas = name.strip_prefix("Ratio")?;
+    let gases = &enums.basic_enums.get("GasType")?.values;
+    if gases.contains_key(gas) {
+        return Some(gas);
+    }
+    let base = NETWORK_SUFFIXES.iter().find_map(|s| gas.strip_suffix(s))?;
+    gases.contains_key(base).then_some(base)
+}
+
+impl LogicTypeInfo {
+    /// Look up what the value of the logic type `name` means, on the device `prefab_name` if given.
+    pub fn resolve(
+        enums: &Enums,
+        config: &Config,
+        prefab_name: Option<&str>,
+        name: &str,
+    ) -> color_eyre::Result<LogicTypeInfo> {
+        let mut info = if let Some((_, ty, unit, range)) = built_in(name) {
+            LogicTypeInfo {
+                ty: *ty,
+                unit: unit.map(str::to_owned),
+                range: range.map(str::to_owned),
+                values: vec![],
+                source: Source::BuiltIn,
+            }
+        } else if ratio_gas(enums, name).is_some() {
+            LogicTypeInfo {
+                ty: Float,
+                unit: None,
+                range: Some("0.0 to 1.0".to_owned()),
+                values: vec![],
+                source: Source::GasRatio,
+            }
+        } else if name.contains("Ratio") || name.contains("Pressure") {
+            LogicTypeInfo {
+                ty: Float,
+                unit: None,
+                range: name.contains("Ratio").then(|| "0.0 to 1.0".to_owned()),
+                values: vec![],
+                source: Source::Heuristic,
+            }
+        } else {
+            LogicTypeInfo {
+                ty: Integer,
+                unit: None,
+                range: None,
+                values: vec![],
+                source: Source::Heuristic,
+            }
+        };
+
+        // device overrides go on top of the global ones
+        let overrides = [
+            config.logic.types.get(name),
+            prefab_name.and_then(|p| config.device_logic_type(p, name)),
+        ];
+        for conf in overrides.into_iter().flatten() {
+            if let Some(ty) = conf.ty {
+                info.ty = ty;
+                info.source = Source::Config;
+            }
+            if let Some(unit) = &conf.unit {
+                info.unit = Some(unit.clone());
+            }
+            if let Some(range) = &conf.range {
+                info.range = Some(range.clone());
+            }
+        }
+        if info.ty == Boolean && info.range.is_none() {
+            info.range = Some("0 or 1".to_owned());
+        }
+
+        if let Some((_, listing)) = ENUM_VALUES.iter().find(|(n, _)| *n == name) {
+            let listing = enums
+                .basic_enums
+                .get(*listing)
+                .ok_or_else(|| eyre::eyre!("no enum `{listing}` for logic type `{name}`"))?;
+            info.values = listing
+                .values
+                .iter()
+                .map(|(n, e)| (e.value, n.clone()))
+                .collect();
+            info.values.sort();
+        }
+        Ok(info)
+    }
+}
+
+/// [`LogicTypeInfo`] for every `LogicType` in [`Enums`] that isn't deprecated.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogicTypes(BTreeMap<String, LogicTypeInfo>);
+
+impl LogicTypes {
+    pub fn new(enums: &Enums, config: &Config) -> color_eyre::Result<Self> {
+        let mut types = BTreeMap::new();
+        if let Some(listing) = enums.script_enums.get("LogicType") {
+            for (name, _) in listing.values.iter().filter(|(_, e)| !e.deprecated) {
+                types.insert(
+                    name.clone(),
+                    LogicTypeInfo::resolve(enums, config, None, name)?,
+                );
+            }
+        }
+        Ok(LogicTypes(types))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LogicTypeInfo> {
+        self.0.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &LogicTypeInfo)> {
+        self.0.iter()
+    }
+
+    /// Logic types that are still typed by guessing from their name.
+    pub fn fallbacks(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter(|(_, i)| i.source == Source::Heuristic)
+            .map(|(n, _)| n.as_str())
+    }
+}
@@ -1,6 +1,12 @@
 mod commands;
+pub mod config;
+pub mod diagnostics;
 pub mod enums;
+pub mod merge;
 pub mod stationpedia;
+#[cfg(feature = "mw")]
+pub mod wiki;
+pub mod wikitext;
 
 use std::{
     path::{Path, PathBuf},
@@ -8,7 +14,7 @@ use std::{
 };
 
 use clap::Parser;
-use color_eyre::eyre;
+use color_eyre::eyre::{self, WrapErr};
 
 #[derive(Debug, Parser)]
 struct Cli {
@@ -22,6 +28,10 @@ struct Cli {
     enums: PathBuf,
     #[arg(global = true, long, default_value = get_cargo_workspace().join("config.toml").into_os_string())]
     config: PathBuf,
+    /// Refuse to run if `Stationpedia.json` and `Enums.json` are from different game versions. Dumps without a version
+    /// can't be checked, older extractors don't write one
+    #[arg(global = true, long)]
+    deny_version_mismatch: bool,
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -34,18 +44,52 @@ fn main() -> color_eyre::Result<()> {
         .init();
     let cli = Cli::parse();
 
-    let pedia: stationpedia::Stationpedia =
-        parse_json(&mut serde_json::Deserializer::from_reader(
-            std::io::BufReader::new(std::fs::File::open(&cli.stationpedia)?),
-        ))?;
-    let enums: enums::Enums = parse_json(&mut serde_json::Deserializer::from_reader(
-        std::io::BufReader::new(std::fs::File::open(&cli.enums)?),
-    ))?;
-    let config = std::fs::read_to_string(&cli.config)?.parse::<toml_edit::DocumentMut>()?;
+    let pedia: stationpedia::Stationpedia = load_json(&cli.stationpedia)?;
+    // build the lookup index up front, generation does lots of lookups
+    pedia.index();
+    let enums: enums::Enums = load_json(&cli.enums)?;
+    check_versions(&pedia, &enums, cli.deny_version_mismatch)?;
+    let config = config::Config::load(&cli.config)?;
     cli.run_subcommand(&pedia, &enums, &config)?;
     Ok(())
 }
 
+/// Check that the stationpedia and enums come from the same extractor run.
+///
+/// A mismatch is warned about, or refused with `deny`. Dumps without a version, like the bundled `Enums.json`,
+/// can't be checked and are only logged at debug level.
+fn check_versions(
+    pedia: &stationpedia::Stationpedia,
+    enums: &enums::Enums,
+    deny: bool,
+) -> color_eyre::Result<()> {
+    let message = match (&pedia.version, &enums.version) {
+        (Some(pedia), Some(enums)) if pedia == enums => return Ok(()),
+        (Some(pedia), Some(enums)) => {
+            format!("Stationpedia.json is from game version {pedia} but Enums.json is from {enums}")
+        }
+        _ => {
+            tracing::debug!("Stationpedia.json or Enums.json has no version, can't check that they match");
+            return Ok(());
+        }
+    };
+    if deny {
+        eyre::bail!(message);
+    }
+    tracing::warn!("{message}");
+    Ok(())
+}
+
+/// Open and parse a json file, see [`parse_json`].
+pub fn load_json<T: serde::de::DeserializeOwned>(path: &Path) -> color_eyre::Result<T> {
+    let file =
+        std::fs::File::open(path).wrap_err_with(|| format!("could not open {}", path.display()))?;
+    parse_json(&mut serde_json::Deserializer::from_reader(
+        std::io::BufReader::new(file),
+    ))
+    .wrap_err_with(|| format!("could not parse {}", path.display()))
+}
+
 #[track_caller]
 pub fn parse_json<'a, T: serde::Deserialize<'a>>(
     jd: impl serde::Deserializer<'a>,
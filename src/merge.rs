@@ -0,0 +1,467 @@
+//! Splice generated output into an existing page, leaving everything hand-written around it alone.
+//!
+//! The regions replaced are the generated templates in [`TEMPLATES`], wherever they are on the page, the tables of
+//! generated sections in the section of the same name on the page, and anything between
+//! `<!-- wiki-dumper:begin NAME -->` and `<!-- wiki-dumper:end NAME -->`. A marked region gets the body of the
+//! generated `== NAME ==` section, or the generated template called `NAME`. The fields of [`INFOBOXES`] are set one
+//! by one instead, so fields added by hand stay.
+
+use std::{ops::Range, sync::OnceLock};
+
+use color_eyre::eyre;
+
+use crate::wikitext;
+
+/// Templates put on pages by the generators.
+pub const TEMPLATES: &[&str] = &[
+    "Itembox",
+    "Structurebox",
+    "Recipe",
+    "Slots",
+    "Connections",
+    "Data Parameters",
+    "Memory",
+];
+
+/// Templates in [`TEMPLATES`] that are updated field by field.
+pub const INFOBOXES: &[&str] = &["Itembox", "Structurebox"];
+
+/// Infobox fields that are only generated for pages that don't have them yet, editors often replace the render with a
+/// better picture.
+const KEPT_FIELDS: &[&str] = &["image"];
+
+/// Fields the generators put in each of the [`INFOBOXES`], `#` stands for a number. These are removed from a page
+/// when they're no longer generated, like the fields of a recipe that was taken out of the game.
+const GENERATED_FIELDS: &[(&str, &[&str])] = &[
+    (
+        "Itembox",
+        &[
+            "name",
+            "image",
+            "prefabhash",
+            "prefabname",
+            "stacks",
+            "slot_class",
+            "sorting_class",
+            "recipe_machine#",
+            "recipe_cost#",
+            "constructs",
+            "nutrition",
+            "quality",
+            "moodbonus",
+            "growthtime",
+        ],
+    ),
+    (
+        "Structurebox",
+        &[
+            "name",
+            "image",
+            "prefab_hash",
+            "prefab_name",
+            "power_usage",
+            "placed_on_grid",
+            "placed_with_item",
+            "decon_with_tool#",
+            "const_with_tool#",
+            "const_with_item#",
+            "item_rec#",
+        ],
+    ),
+];
+
+/// The result of [`merge`].
+#[derive(Debug, Default)]
+pub struct Merged {
+    pub text: String,
+    /// Regions that were replaced
+    pub replaced: Vec<String>,
+    /// Generated templates and section tables the page has no place for
+    pub unplaced: Vec<String>,
+    /// Marked regions on the page with nothing generated for them
+    pub unmatched: Vec<String>,
+}
+
+impl Merged {
+    /// Warn about the regions that didn't line up between the page and the generated output.
+    pub fn warn(&self, title: &str) {
+        for name in &self.unplaced {
+            tracing::warn!("{title}: nowhere on the page to put {name}, add it or a `wiki-dumper:begin {name}` marker");
+        }
+        for name in &self.unmatched {
+            tracing::warn!("{title}: nothing generated for the `wiki-dumper:begin {name}` marker");
+        }
+    }
+}
+
+/// Byte range of the first call of the template `name` in `text`.
+fn template(text: &str, name: &str) -> Option<Range<usize>> {
+    wikitext::find(text, name).map(|(range, _)| range)
+}
+
+/// Body of the `== name ==` section in `text`, of any level, up to the next heading of the same or a higher level.
+fn section<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let found = headings(text);
+    let i = found.iter().position(|(_, heading, _)| *heading == name)?;
+    let (level, _, range) = &found[i];
+    // the subsections are part of it
+    let end = found[i + 1..]
+        .iter()
+        .take_while(|(sub, ..)| sub > level)
+        .last()
+        .map_or(range.end, |(.., sub)| sub.end);
+    Some(text[range.start..end].trim_matches('\n'))
+}
+
+/// Levels and names of the headings in `text`, and the ranges of the text under them up to the next heading of any
+/// level.
+fn headings(text: &str) -> Vec<(usize, &str, Range<usize>)> {
+    static HEADING: OnceLock<regex::Regex> = OnceLock::new();
+    let heading = HEADING
+        .get_or_init(|| regex::Regex::new(r"(?m)^(=+)[ \t]*(.+?)[ \t]*(=+)[ \t]*$").unwrap());
+    let found = heading
+        .captures_iter(text)
+        .filter(|c| c[1].len() == c[3].len())
+        .filter_map(|c| Some((c[1].len(), c.get(2)?.as_str(), c.get(0)?.range())))
+        .collect::<Vec<_>>();
+    found
+        .iter()
+        .enumerate()
+        .map(|(i, (level, name, range))| {
+            let end = found.get(i + 1).map_or(text.len(), |(.., next)| next.start);
+            (*level, *name, range.end..end)
+        })
+        .collect()
+}
+
+/// Byte ranges of the tables in `range` of `text`, from the `{|` line to the `|}` line, nested tables are part of
+/// the one around them.
+fn tables(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut found = vec![];
+    let (mut depth, mut start) = (0, 0);
+    let mut at = range.start;
+    for line in text[range].split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("{|") {
+            if depth == 0 {
+                start = at;
+            }
+            depth += 1;
+        } else if trimmed.starts_with("|}") && depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                found.push(start..at + line.trim_end().len());
+            }
+        }
+        at += line.len();
+    }
+    found
+}
+
+/// Whether the generators put the field `key` in the infobox `name`, see [`GENERATED_FIELDS`].
+fn is_generated_field(name: &str, key: &str) -> bool {
+    let fields = GENERATED_FIELDS
+        .iter()
+        .find(|(infobox, _)| *infobox == name)
+        .map_or(&[][..], |(_, fields)| fields);
+    fields.iter().any(|field| match field.strip_suffix('#') {
+        Some(prefix) => key
+            .strip_prefix(prefix)
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())),
+        None => key == *field,
+    })
+}
+
+/// `existing` with the fields of `generated` set on it, keeping the fields only on the page and the [`KEPT_FIELDS`]
+/// that have a value. Generated fields that `generated` doesn't have any more are removed.
+fn merge_fields(existing: &str, generated: &str) -> color_eyre::Result<String> {
+    let mut merged = wikitext::Template::parse(existing)?;
+    let generated = wikitext::Template::parse(generated)?;
+    for (key, value) in generated.params() {
+        if KEPT_FIELDS.contains(&key.as_str()) && merged.get(&key).is_some_and(|v| !v.is_empty()) {
+            continue;
+        }
+        merged.set(&key, value);
+    }
+    let stale = merged
+        .params()
+        .map(|(key, _)| key)
+        .filter(|key| {
+            is_generated_field(generated.name(), key)
+                && !KEPT_FIELDS.contains(&key.as_str())
+                && generated.get(key).is_none()
+        })
+        .collect::<Vec<_>>();
+    for key in stale {
+        merged.remove(&key);
+    }
+    Ok(merged.to_string())
+}
+
+/// Names and content ranges of the `<!-- wiki-dumper:begin NAME -->` regions in `text`.
+fn markers(text: &str) -> color_eyre::Result<Vec<(String, Range<usize>)>> {
+    static BEGIN: OnceLock<regex::Regex> = OnceLock::new();
+    let begin =
+        BEGIN.get_or_init(|| regex::Regex::new(r"<!--\s*wiki-dumper:begin\s+(.+?)\s*-->").unwrap());
+    let mut regions = vec![];
+    let mut from = 0;
+    while let Some(c) = begin.captures_at(text, from) {
+        let name = c[1].to_owned();
+        let start = c.get(0).map_or(from, |m| m.end());
+        let end = regex::Regex::new(&format!(
+            r"<!--\s*wiki-dumper:end\s+{}\s*-->",
+            regex::escape(&name)
+        ))?;
+        let Some(end) = end.find_at(text, start) else {
+            eyre::bail!("`wiki-dumper:begin {name}` has no matching `wiki-dumper:end {name}`");
+        };
+        regions.push((name, start..end.start()));
+        from = end.end();
+    }
+    Ok(regions)
+}
+
+/// The generated section or template called `name`.
+fn generated_region<'a>(generated: &'a str, name: &str) -> Option<&'a str> {
+    section(generated, name).or_else(|| template(generated, name).map(|range| &generated[range]))
+}
+
+/// Replace the generated regions of `existing` with the ones in `generated`.
+///
+/// The provenance comment of `generated` replaces the one on the page, or is put at the top if anything was replaced.
+pub fn merge(existing: &str, generated: &str) -> color_eyre::Result<Merged> {
+    let mut merged = Merged::default();
+    let mut edits = vec![];
+
+    let marked = markers(existing)?;
+    let is_marked = |range: &Range<usize>| {
+        marked
+            .iter()
+            .any(|(_, m)| m.start <= range.start && range.end <= m.end)
+    };
+    for (name, range) in &marked {
+        match generated_region(generated, name) {
+            Some(region) => {
+                edits.push((range.clone(), format!("\n{region}\n")));
+                merged.replaced.push(name.clone());
+            }
+            None => merged.unmatched.push(name.clone()),
+        }
+    }
+    for name in TEMPLATES {
+        let Some(region) = template(generated, name).map(|r| &generated[r]) else {
+            continue;
+        };
+        if marked.iter().any(|(marker, _)| marker == name) {
+            continue;
+        }
+        match template(existing, name) {
+            // a template inside a marked region is replaced along with it
+            Some(range) if !is_marked(&range) => {
+                let replacement = if INFOBOXES.contains(name) {
+                    merge_fields(&existing[range.clone()], region)?
+                } else {
+                    region.to_owned()
+                };
+                edits.push((range, replacement));
+                merged.replaced.push(name.to_string());
+            }
+            Some(_) => {}
+            None => merged.unplaced.push(name.to_string()),
+        }
+    }
+    let sections = headings(existing);
+    // subsections are reported as the top-level section they're in, so a missing section is only reported once
+    let mut top = "";
+    for (level, name, range) in headings(generated) {
+        if level <= 2 || top.is_empty() {
+            top = name;
+        }
+        let new = tables(generated, range);
+        if new.is_empty()
+            || marked
+                .iter()
+                .any(|(marker, _)| marker == name || marker == top)
+        {
+            continue;
+        }
+        let old = sections
+            .iter()
+            .find(|(_, heading, _)| *heading == name)
+            .map(|(.., range)| tables(existing, range.clone()))
+            .unwrap_or_default();
+        if old.iter().any(&is_marked) {
+            continue;
+        }
+        // tables are paired up in order, so the section needs as many as are generated
+        if old.len() != new.len() {
+            if !merged.unplaced.iter().any(|n| n == top) {
+                merged.unplaced.push(top.to_owned());
+            }
+            continue;
+        }
+        for (old, new) in old.into_iter().zip(new) {
+            edits.push((old, generated[new].to_owned()));
+        }
+        merged.replaced.push(name.to_owned());
+    }
+
+    if !merged.replaced.is_empty() {
+        if let Some(provenance) = provenance_range(generated) {
+            match provenance_range(existing) {
+                Some(range) if is_marked(&range) => {}
+                Some(range) => edits.push((range, generated[provenance].to_owned())),
+                None => edits.push((0..0, format!("{}\n", &generated[provenance]))),
+            }
+        }
+    }
+
+    // an insertion sorts before a replacement starting at the same place, so it's applied after it
+    edits.sort_by_key(|(range, _)| (range.start, range.end));
+    for pair in edits.windows(2) {
+        if pair[0].0.end > pair[1].0.start {
+            eyre::bail!("generated regions overlap on the page");
+        }
+    }
+    let mut text = existing.to_owned();
+    for (range, replacement) in edits.into_iter().rev() {
+        text.replace_range(range, &replacement);
+    }
+    merged.text = text;
+    Ok(merged)
+}
+
+/// Byte range of the `<!-- Generated by ... -->` comment in `text`.
+fn provenance_range(text: &str) -> Option<Range<usize>> {
+    let start = text.find("<!-- Generated by ")?;
+    let end = start + text[start..].find("-->")? + "-->".len();
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str =
+        "<!-- Generated by wiki-dumper 0.1.0 from Stationeers 0.2.5108.23217 -->
+{{Itembox
+| name = Ingot (Iron)
+| image = [[File:ItemIronIngot.png]]
+| stacks = 500
+}}
+
+== Used in ==
+{| class=\"wikitable\"
+! Product
+|-
+| [[Iron Sheets]]
+|}
+
+== Memory ==
+{{Memory
+| size = 64
+}}
+
+=== StackPointer ===
+{| class=\"wikitable\"
+! Bits
+|}
+";
+
+    #[test]
+    fn infobox_fields() {
+        let existing = "{{Itembox
+| name = Ingot (Iron)
+| image = [[File:Custom.png]]
+| stacks = 50
+| notes = smelted from [[Ore (Iron)]]
+| recipe_machine1 = Furnace
+| recipe_cost1 = 1g [[Iron]]
+}}
+";
+        let merged = merge(existing, GENERATED).unwrap();
+        // the recipe isn't generated any more, so its fields are gone
+        assert!(merged.text.contains(
+            "| image = [[File:Custom.png]]\n| stacks = 500\n| notes = smelted from [[Ore (Iron)]]\n}}"
+        ));
+        assert_eq!(merged.unplaced, ["Memory", "Used in"]);
+        assert_eq!(merge(&merged.text, GENERATED).unwrap().text, merged.text);
+    }
+
+    #[test]
+    fn marked_regions() {
+        let existing = "<!-- wiki-dumper:begin Itembox -->
+{{Itembox
+| name = Iron
+}}
+<!-- wiki-dumper:end Itembox -->
+
+== Used in ==
+<!-- wiki-dumper:begin Used in -->
+{{Memory
+| size = 32
+}}
+<!-- wiki-dumper:end Used in -->
+
+== Trivia ==
+<!-- wiki-dumper:begin Trivia -->
+Heavy.
+<!-- wiki-dumper:end Trivia -->
+";
+        let merged = merge(existing, GENERATED).unwrap();
+        assert_eq!(
+            merged.text,
+            "<!-- Generated by wiki-dumper 0.1.0 from Stationeers 0.2.5108.23217 -->
+<!-- wiki-dumper:begin Itembox -->
+{{Itembox
+| name = Ingot (Iron)
+| image = [[File:ItemIronIngot.png]]
+| stacks = 500
+}}
+<!-- wiki-dumper:end Itembox -->
+
+== Used in ==
+<!-- wiki-dumper:begin Used in -->
+{| class=\"wikitable\"
+! Product
+|-
+| [[Iron Sheets]]
+|}
+<!-- wiki-dumper:end Used in -->
+
+== Trivia ==
+<!-- wiki-dumper:begin Trivia -->
+Heavy.
+<!-- wiki-dumper:end Trivia -->
+"
+        );
+        assert_eq!(merged.replaced, ["Itembox", "Used in"]);
+        // the `{{Memory}}` in the `Used in` region isn't replaced on its own
+        assert_eq!(merged.unplaced, ["Memory"]);
+        assert_eq!(merged.unmatched, ["Trivia"]);
+
+        let unclosed = "<!-- wiki-dumper:begin Used in -->\n<!-- wiki-dumper:end Memory -->\n";
+        assert!(merge(unclosed, GENERATED).is_err());
+    }
+
+    #[test]
+    fn section_tables() {
+        let existing = "== Used in ==
+Also used to make things by hand.
+{| class=\"wikitable\"
+! Product
+|-
+| [[Iron Frames]]
+|}
+
+== Trivia ==
+Heavy.
+";
+        let merged = merge(existing, GENERATED).unwrap();
+        assert!(merged.text.contains(
+            "Also used to make things by hand.\n{| class=\"wikitable\"\n! Product\n|-\n| [[Iron Sheets]]\n|}\n\n"
+        ));
+        assert_eq!(merged.replaced, ["Used in"]);
+        assert_eq!(merged.unplaced, ["Itembox", "Memory"]);
+    }
+}
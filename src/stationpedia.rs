@@ -1,14 +1,90 @@
+use std::sync::OnceLock;
+
 use indexmap::IndexMap;
 use serde_derive::{Deserialize, Serialize};
 
+mod index;
+mod memory;
+mod recipe_index;
+
+pub use index::PageIndex;
+pub use memory::{BitField, InstructionLayout};
+pub use recipe_index::{RecipeIndex, RecipeUse};
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename = "Stationpedia")]
 pub struct Stationpedia {
+    /// Version of the game the stationpedia was extracted from, older extractors don't write this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<GameVersion>,
     pub pages: Vec<Page>,
     pub reagents: IndexMap<String, Reagent>,
     #[serde(rename = "scriptCommands")]
     pub script_commands: std::collections::BTreeMap<String, Command>,
     pub core_prefabs: Vec<CorePrefab>,
+    /// Lookup tables over `pages`, built on first use.
+    #[serde(skip)]
+    index: OnceLock<PageIndex>,
+    /// Reverse lookup of recipes by ingredient, built on first use.
+    #[serde(skip)]
+    recipe_index: OnceLock<RecipeIndex>,
+}
+
+/// A game version, like `0.2.5108.23217`
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GameVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+    pub revision: u32,
+}
+
+impl std::str::FromStr for GameVersion {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split('.')
+            .map(|p| p.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| color_eyre::eyre::eyre!("invalid game version `{s}`: {e}"))?;
+        let [major, minor, build, revision] = parts[..] else {
+            color_eyre::eyre::bail!("invalid game version `{s}`: expected four components");
+        };
+        Ok(Self {
+            major,
+            minor,
+            build,
+            revision,
+        })
+    }
+}
+
+impl TryFrom<String> for GameVersion {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<GameVersion> for String {
+    fn from(value: GameVersion) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let GameVersion {
+            major,
+            minor,
+            build,
+            revision,
+        } = self;
+        write!(f, "{major}.{minor}.{build}.{revision}")
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -119,13 +195,23 @@ pub struct CorePrefab {
     #[serde(rename = "Thermal")]
     pub thermal: Option<CorePrefabThermal>,
     #[serde(rename = "Organ")]
-    pub organ: Option<serde_json::value::Value>,
+    pub organ: Option<Organ>,
     #[serde(rename = "Lungs")]
     pub lungs: Option<Lungs>,
     #[serde(rename = "Brain")]
-    pub brain: Option<serde_json::value::Value>,
+    pub brain: Option<Brain>,
 }
 
+/// Marks a core prefab as an organ that goes in an `Organ` slot of the character.
+///
+/// Empty in current dumps, any keys the game adds are reported as unknown when loading.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Organ {}
+
+/// Marks an organ as the brain. Empty in current dumps, like [`Organ`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Brain {}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Lungs {
     #[serde(rename = "BreathableType")]
@@ -141,16 +227,71 @@ pub struct Lungs {
 }
 
 impl Stationpedia {
+    /// The lookup index over `pages`.
+    ///
+    /// Built once, so `pages` should not be modified after the first lookup.
+    pub fn index(&self) -> &PageIndex {
+        self.index.get_or_init(|| PageIndex::new(&self.pages))
+    }
+
     pub fn lookup_prefab_name(&self, prefab_name: &'_ str) -> Option<&Page> {
-        self.pages.iter().find(|p| p.prefab_name == prefab_name)
+        self.index()
+            .prefab_name(prefab_name)
+            .map(|i| &self.pages[i])
     }
 
     pub fn lookup_key(&self, key: &str) -> Option<&Page> {
-        self.pages.iter().find(|p| p.key == key)
+        self.index().key(key).map(|i| &self.pages[i])
     }
 
     pub fn lookup_hash(&self, hash: i64) -> Option<&Page> {
-        self.pages.iter().find(|p| p.prefab_hash == hash)
+        self.index().hash(hash).map(|i| &self.pages[i])
+    }
+
+    pub fn lookup_title(&self, title: &str) -> Option<&Page> {
+        self.index().title(title).map(|i| &self.pages[i])
+    }
+
+    /// Like [`Stationpedia::lookup_title`], but ignoring case.
+    pub fn lookup_title_ignore_case(&self, title: &str) -> Option<&Page> {
+        self.index()
+            .title_ignore_case(title)
+            .map(|i| &self.pages[i])
+    }
+
+    /// The reverse recipe index over `pages`, see [`Stationpedia::index`].
+    pub fn recipe_index(&self) -> &RecipeIndex {
+        self.recipe_index
+            .get_or_init(|| RecipeIndex::new(&self.pages))
+    }
+
+    /// All recipes consuming `reagent`.
+    pub fn recipes_using_reagent(&self, reagent: &str) -> impl Iterator<Item = &RecipeUse> {
+        self.recipe_index().reagent(reagent)
+    }
+
+    /// All recipes consuming the prefab, with the reagent it provides.
+    ///
+    /// Only recipes made in a machine that accepts the prefab are included, so ores don't show up in recipes needing ingots.
+    pub fn recipes_using_prefab(&self, prefab_name: &str) -> Vec<(&str, &RecipeUse)> {
+        let Some(reagents) = self
+            .lookup_prefab_name(prefab_name)
+            .and_then(|p| p.item.as_ref()?.reagents.as_ref())
+        else {
+            return vec![];
+        };
+        reagents
+            .keys()
+            .flat_map(|reagent| {
+                self.recipes_using_reagent(reagent)
+                    .map(move |r| (reagent.as_str(), r))
+            })
+            .filter(|(_, r)| {
+                self.lookup_prefab_name(&r.recipe.creator_prefab_name)
+                    .and_then(|c| c.resource_consumer.as_ref())
+                    .is_none_or(|rc| rc.consumed_resources.iter().any(|c| c == prefab_name))
+            })
+            .collect()
     }
 }
 
@@ -164,6 +305,17 @@ pub struct Reagent {
     pub sources: Option<IndexMap<String, f64>>,
 }
 
+impl Reagent {
+    /// Suffix for an amount of this reagent, like `10g`. Reagents without a unit are counted, like `2 x`.
+    pub fn unit_suffix(&self) -> &str {
+        if self.unit.is_empty() {
+            " x"
+        } else {
+            &self.unit
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
 pub struct Command {
     pub desc: String,
@@ -299,6 +451,35 @@ pub enum MachineTier {
     Max,
 }
 
+impl MachineTier {
+    /// The name used in the stationpedia, e.g. in `Recipe.TierName`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MachineTier::Undefined => "Undefined",
+            MachineTier::TierOne => "TierOne",
+            MachineTier::TierTwo => "TierTwo",
+            MachineTier::TierThree => "TierThree",
+            MachineTier::Max => "Max",
+        }
+    }
+}
+
+impl std::str::FromStr for MachineTier {
+    type Err = color_eyre::Report;
+
+    /// Accepts the stationpedia name, like `TierTwo`, or just the number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "undefined" => MachineTier::Undefined,
+            "tierone" | "one" | "1" => MachineTier::TierOne,
+            "tiertwo" | "two" | "2" => MachineTier::TierTwo,
+            "tierthree" | "three" | "3" => MachineTier::TierThree,
+            "max" => MachineTier::Max,
+            _ => color_eyre::eyre::bail!("invalid machine tier `{s}`"),
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
 pub struct Tool {
     #[serde(rename = "IsTool", default)]
@@ -0,0 +1,57 @@
+//! Prebuilt lookup tables over [`Stationpedia::pages`](super::Stationpedia::pages).
+
+use std::collections::HashMap;
+
+use super::Page;
+
+/// Maps the different ways of referring to a page to its position in `pages`.
+///
+/// When multiple pages share a key, the first one wins, matching what a linear `find` would return.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PageIndex {
+    prefab_name: HashMap<String, usize>,
+    key: HashMap<String, usize>,
+    hash: HashMap<i64, usize>,
+    title: HashMap<String, usize>,
+    title_lowercase: HashMap<String, usize>,
+}
+
+impl PageIndex {
+    pub fn new(pages: &[Page]) -> Self {
+        let mut index = Self::default();
+        for (i, page) in pages.iter().enumerate() {
+            index
+                .prefab_name
+                .entry(page.prefab_name.clone())
+                .or_insert(i);
+            index.key.entry(page.key.clone()).or_insert(i);
+            index.hash.entry(page.prefab_hash).or_insert(i);
+            index.title.entry(page.title.clone()).or_insert(i);
+            index
+                .title_lowercase
+                .entry(page.title.to_lowercase())
+                .or_insert(i);
+        }
+        index
+    }
+
+    pub fn prefab_name(&self, prefab_name: &str) -> Option<usize> {
+        self.prefab_name.get(prefab_name).copied()
+    }
+
+    pub fn key(&self, key: &str) -> Option<usize> {
+        self.key.get(key).copied()
+    }
+
+    pub fn hash(&self, hash: i64) -> Option<usize> {
+        self.hash.get(&hash).copied()
+    }
+
+    pub fn title(&self, title: &str) -> Option<usize> {
+        self.title.get(title).copied()
+    }
+
+    pub fn title_ignore_case(&self, title: &str) -> Option<usize> {
+        self.title_lowercase.get(&title.to_lowercase()).copied()
+    }
+}
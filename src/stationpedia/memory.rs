@@ -0,0 +1,181 @@
+//! Bit layouts of device memory instructions.
+//!
+//! [`Instruction::description`] is an ASCII table drawn with pipes and `<color>` tags, like
+//! `| <color=red>0-7 </color> | <color=yellow>OP_CODE</color> | <color=orange>BYTE_8</color> |`,
+//! one row per bit field, optionally preceded by a single-cell row with a note such as `VALID ONLY AT ADDRESS 63`.
+
+use std::sync::OnceLock;
+
+use color_eyre::eyre;
+
+use super::Instruction;
+
+/// A field of an instruction word, covering bits `start..=end`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitField {
+    pub start: u32,
+    pub end: u32,
+    pub name: String,
+    /// Type of the field, like `BYTE_8` or `INT_32`, or the number of bits for unused fields
+    pub ty: String,
+}
+
+impl BitField {
+    pub fn bits(&self) -> u32 {
+        self.end - self.start + 1
+    }
+
+    pub fn is_unused(&self) -> bool {
+        self.name == "UNUSED"
+    }
+
+    /// Whether the field holds a two's complement value, like `INT_32` or `SHORT_16`.
+    pub fn is_signed(&self) -> bool {
+        self.ty.starts_with("INT_") || self.ty.starts_with("SHORT_") || self.ty.starts_with("LONG_")
+    }
+
+    /// Smallest and largest value that fits in the field.
+    pub fn range(&self) -> (i128, i128) {
+        let bits = self.bits();
+        if self.is_signed() {
+            (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+        } else {
+            (0, (1 << bits) - 1)
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.bits())
+    }
+
+    /// The value of this field in `word`.
+    pub fn get(&self, word: u64) -> i64 {
+        let raw = (word >> self.start) & self.mask();
+        if self.is_signed() {
+            // sign extend
+            let shift = 64 - self.bits();
+            ((raw << shift) as i64) >> shift
+        } else {
+            raw as i64
+        }
+    }
+
+    /// Set this field in `word` to `value`.
+    pub fn set(&self, word: u64, value: i64) -> color_eyre::Result<u64> {
+        let (min, max) = self.range();
+        if !(min..=max).contains(&(value as i128)) {
+            eyre::bail!(
+                "{} = {value} doesn't fit in {}, expected {min} to {max}",
+                self.name,
+                self.ty
+            );
+        }
+        let raw = value as u64 & self.mask();
+        Ok(word & !(self.mask() << self.start) | raw << self.start)
+    }
+}
+
+/// The parsed layout of an [`Instruction`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstructionLayout {
+    /// Single-cell rows, like `VALID ONLY AT ADDRESS 63`
+    pub notes: Vec<String>,
+    pub fields: Vec<BitField>,
+}
+
+/// Name of the field holding the instruction's `Value`.
+pub const OP_CODE: &str = "OP_CODE";
+
+impl InstructionLayout {
+    /// Pack an instruction word, with the opcode field set to `opcode` and unused fields zeroed.
+    ///
+    /// Every other field needs a value, field names are matched ignoring case.
+    pub fn encode(&self, opcode: i64, values: &[(String, i64)]) -> color_eyre::Result<u64> {
+        for (name, _) in values {
+            if !self
+                .fields
+                .iter()
+                .any(|f| !f.is_unused() && f.name != OP_CODE && f.name.eq_ignore_ascii_case(name))
+            {
+                eyre::bail!("no field `{name}` in instruction");
+            }
+        }
+        let mut word = 0;
+        for field in &self.fields {
+            if field.is_unused() {
+                continue;
+            }
+            let value = if field.name == OP_CODE {
+                opcode
+            } else {
+                values
+                    .iter()
+                    .find(|(n, _)| field.name.eq_ignore_ascii_case(n))
+                    .map(|(_, v)| *v)
+                    .ok_or_else(|| eyre::eyre!("missing value for field `{}`", field.name))?
+            };
+            word = field.set(word, value)?;
+        }
+        Ok(word)
+    }
+
+    /// The value of each used field in `word`.
+    pub fn decode(&self, word: u64) -> Vec<(&BitField, i64)> {
+        self.fields
+            .iter()
+            .filter(|f| !f.is_unused())
+            .map(|f| (f, f.get(word)))
+            .collect()
+    }
+
+    /// The opcode in `word`, if the layout has an opcode field.
+    pub fn opcode(&self, word: u64) -> Option<i64> {
+        self.fields
+            .iter()
+            .find(|f| f.name == OP_CODE)
+            .map(|f| f.get(word))
+    }
+}
+
+impl Instruction {
+    /// Parse the bit layout table in the description.
+    pub fn layout(&self) -> color_eyre::Result<InstructionLayout> {
+        static COLOR: OnceLock<regex::Regex> = OnceLock::new();
+        let re = COLOR.get_or_init(|| regex::Regex::new(r"<color=.*?>|</color>").unwrap());
+        let mut layout = InstructionLayout::default();
+        for line in re.replace_all(&self.description, "").lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let cells = line
+                .trim_matches('|')
+                .split('|')
+                .map(str::trim)
+                .collect::<Vec<_>>();
+            match cells[..] {
+                [note] => layout.notes.push(note.to_owned()),
+                [bits, name, ty] => {
+                    let (start, end) = bits
+                        .split_once('-')
+                        .ok_or_else(|| eyre::eyre!("invalid bit range `{bits}`"))?;
+                    let (start, end) = (start.trim().parse()?, end.trim().parse()?);
+                    if start > end || end > 63 {
+                        eyre::bail!("invalid bit range `{bits}`");
+                    }
+                    layout.fields.push(BitField {
+                        start,
+                        end,
+                        name: name.to_owned(),
+                        ty: ty.to_owned(),
+                    });
+                }
+                _ => eyre::bail!("unexpected row in instruction layout: `{line}`"),
+            }
+        }
+        if layout.fields.is_empty() {
+            eyre::bail!("no bit fields in instruction layout");
+        }
+        Ok(layout)
+    }
+}
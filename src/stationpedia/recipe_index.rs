@@ -0,0 +1,72 @@
+//! Reverse lookup from reagents to the recipes consuming them.
+
+use std::collections::HashMap;
+
+use super::{Page, Recipe};
+
+/// A recipe and the prefab it produces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecipeUse {
+    /// Prefab name of the product
+    pub product: String,
+    pub recipe: Recipe,
+}
+
+/// Maps each reagent to the recipes consuming it, over both `Item.Recipes` and `Fabricator.Recipes`.
+///
+/// The same recipe is usually listed on both the product and the fabricator, so identical recipes are only kept once.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecipeIndex {
+    recipes: Vec<RecipeUse>,
+    by_reagent: HashMap<String, Vec<usize>>,
+}
+
+impl RecipeIndex {
+    pub fn new(pages: &[Page]) -> Self {
+        let mut index = Self::default();
+        let item_recipes = pages.iter().flat_map(|page| {
+            page.item
+                .iter()
+                .flat_map(|i| &i.recipes)
+                .map(|recipe| (page.prefab_name.as_str(), recipe))
+        });
+        let fabricator_recipes = pages
+            .iter()
+            .filter_map(|page| page.device.as_ref()?.fabricator.as_ref())
+            .flat_map(|f| &f.recipes)
+            .map(|(product, recipe)| (product.as_str(), recipe));
+        // recipes hold floats and can't be hashed, so only compare those of the same product, creator and tier
+        let mut seen = HashMap::<_, Vec<&Recipe>>::new();
+        for (product, recipe) in item_recipes.chain(fabricator_recipes) {
+            let same = seen
+                .entry((
+                    product,
+                    recipe.creator_prefab_name.as_str(),
+                    recipe.tier_name.as_str(),
+                ))
+                .or_default();
+            if same.contains(&recipe) {
+                continue;
+            }
+            same.push(recipe);
+            let i = index.recipes.len();
+            index.recipes.push(RecipeUse {
+                product: product.to_owned(),
+                recipe: recipe.clone(),
+            });
+            for (reagent, _) in recipe.reagents.iter().filter(|(_, q)| *q > &0.0) {
+                index.by_reagent.entry(reagent.clone()).or_default().push(i);
+            }
+        }
+        index
+    }
+
+    /// All recipes with `reagent` as an ingredient.
+    pub fn reagent(&self, reagent: &str) -> impl Iterator<Item = &RecipeUse> {
+        self.by_reagent
+            .get(reagent)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.recipes[i])
+    }
+}
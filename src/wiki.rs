@@ -0,0 +1,223 @@
+#![cfg(feature = "mw")]
+//! Reading and editing pages through the MediaWiki action API, with the endpoint and login from `[bot]` in the config.
+
+use std::fmt;
+
+use color_eyre::eyre::{self, WrapErr};
+
+use crate::config::Config;
+
+/// What to do with a page depending on whether it already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mode {
+    /// Only create pages that don't exist yet, existing pages are skipped
+    CreateOnly,
+    /// Only edit pages that already exist, missing pages are skipped
+    UpdateOnly,
+    /// Create missing pages and replace the text of existing ones
+    Overwrite,
+}
+
+/// What happened, or would happen, to a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Created,
+    Updated,
+    /// The page already has this text
+    Unchanged,
+    /// Left alone because of the [`Mode`]
+    Skipped,
+    /// Not saved because the page was edited, created or deleted since it was fetched
+    Conflict,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Outcome::Created => "created",
+            Outcome::Updated => "updated",
+            Outcome::Unchanged => "unchanged",
+            Outcome::Skipped => "skipped",
+            Outcome::Conflict => "conflict",
+        })
+    }
+}
+
+impl Mode {
+    /// The outcome of saving `text` over `current`, without saving anything.
+    pub fn outcome(self, current: Option<&str>, text: &str) -> Outcome {
+        match (self, current) {
+            (Mode::CreateOnly, Some(_)) | (Mode::UpdateOnly, None) => Outcome::Skipped,
+            // MediaWiki strips trailing whitespace when saving
+            (_, Some(current)) if current.trim_end() == text.trim_end() => Outcome::Unchanged,
+            (_, Some(_)) => Outcome::Updated,
+            (_, None) => Outcome::Created,
+        }
+    }
+}
+
+/// Username and bot password, from the environment or `[bot]` in the config.
+fn credentials(config: &Config) -> color_eyre::Result<(String, String)> {
+    let var = |name: &str, fallback: &Option<String>| {
+        std::env::var(name)
+            .ok()
+            .or_else(|| fallback.clone())
+            .ok_or_else(|| eyre::eyre!("no {name} in the environment or `[bot]` in the config"))
+    };
+    Ok((
+        var("WIKI_DUMPER_USERNAME", &config.bot.username)?,
+        var("WIKI_DUMPER_PASSWORD", &config.bot.password)?,
+    ))
+}
+
+/// A page as fetched by [`Wiki::fetch`], with the timestamps needed to edit it without clobbering newer edits.
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    /// Current wikitext, `None` if the page doesn't exist
+    pub text: Option<String>,
+    /// Timestamp of the current revision
+    base: Option<String>,
+    /// When it was fetched
+    start: String,
+}
+
+pub struct Wiki {
+    client: mwapi::Client,
+}
+
+impl Wiki {
+    /// Connect to `api_url`, or `[bot] api_url` in the config. Editing needs `login`, reading doesn't.
+    pub async fn connect(
+        config: &Config,
+        api_url: Option<&str>,
+        login: bool,
+    ) -> color_eyre::Result<Self> {
+        let api_url = api_url
+            .or(config.bot.api_url.as_deref())
+            .ok_or_else(|| eyre::eyre!("no api url, set `[bot] api_url` in the config"))?;
+        let mut builder = mwapi::Client::builder(api_url).set_user_agent(&format!(
+            "{}/{}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ));
+        if login {
+            let (username, password) = credentials(config)?;
+            builder = builder.set_botpassword(&username, &password);
+        }
+        let client = builder
+            .build()
+            .await
+            .wrap_err_with(|| format!("couldn't connect to {api_url}"))?;
+        Ok(Self { client })
+    }
+
+    /// Current wikitext of a page, `None` if it doesn't exist.
+    pub async fn wikitext(&self, title: &str) -> color_eyre::Result<Option<String>> {
+        Ok(self.fetch(title).await?.text)
+    }
+
+    /// Current wikitext of a page along with its revision timestamp, to pass to [`Wiki::edit`].
+    pub async fn fetch(&self, title: &str) -> color_eyre::Result<Fetched> {
+        let resp = self
+            .client
+            .get_value(&[
+                ("action", "query"),
+                ("prop", "revisions"),
+                ("titles", title),
+                ("rvprop", "content|timestamp"),
+                ("rvslots", "main"),
+                ("curtimestamp", "1"),
+            ])
+            .await
+            .wrap_err_with(|| format!("couldn't fetch {title}"))?;
+        let start = resp["curtimestamp"]
+            .as_str()
+            .ok_or_else(|| eyre::eyre!("no curtimestamp in the response for {title}"))?
+            .to_owned();
+        let page = &resp["query"]["pages"][0];
+        if page.get("invalid").is_some() {
+            eyre::bail!("invalid title {title}: {}", page["invalidreason"]);
+        }
+        if page.get("missing").is_some() {
+            return Ok(Fetched {
+                text: None,
+                base: None,
+                start,
+            });
+        }
+        let revision = &page["revisions"][0];
+        let text = revision["slots"]["main"]["content"]
+            .as_str()
+            .ok_or_else(|| eyre::eyre!("no content for {title} in the response"))?;
+        let base = revision["timestamp"]
+            .as_str()
+            .ok_or_else(|| eyre::eyre!("no revision timestamp for {title} in the response"))?;
+        Ok(Fetched {
+            text: Some(text.to_owned()),
+            base: Some(base.to_owned()),
+            start,
+        })
+    }
+
+    /// Save `text` to `title` as a bot edit, unless `mode` says to skip it or the page already has that text.
+    ///
+    /// `current` is the page from [`Wiki::fetch`]. Its timestamps are passed on, along with `createonly` for a page
+    /// that didn't exist and `nocreate` for one that did, so an edit made since it was fetched isn't clobbered. The
+    /// page is skipped instead if the [`Mode`] wouldn't have edited it either, otherwise that's a
+    /// [`Outcome::Conflict`].
+    pub async fn edit(
+        &self,
+        title: &str,
+        current: &Fetched,
+        text: &str,
+        summary: &str,
+        mode: Mode,
+    ) -> color_eyre::Result<Outcome> {
+        match mode.outcome(current.text.as_deref(), text) {
+            outcome @ (Outcome::Skipped | Outcome::Unchanged | Outcome::Conflict) => {
+                return Ok(outcome)
+            }
+            Outcome::Created | Outcome::Updated => {}
+        }
+        let mut params = vec![
+            ("action", "edit"),
+            ("title", title),
+            ("text", text),
+            ("summary", summary),
+            ("bot", "1"),
+            ("starttimestamp", current.start.as_str()),
+        ];
+        match &current.base {
+            Some(base) => params.extend([("nocreate", "1"), ("basetimestamp", base.as_str())]),
+            None => params.push(("createonly", "1")),
+        }
+        let resp: serde_json::Value = match self.client.post_with_token("csrf", params).await {
+            Ok(resp) => resp,
+            Err(mwapi::Error::ApiError(e)) if e.code == "editconflict" => {
+                return Ok(Outcome::Conflict)
+            }
+            Err(mwapi::Error::ApiError(e))
+                if e.code == "articleexists"
+                    || e.code == "missingtitle"
+                    || e.code == "pagedeleted" =>
+            {
+                return Ok(match mode {
+                    Mode::CreateOnly | Mode::UpdateOnly => Outcome::Skipped,
+                    Mode::Overwrite => Outcome::Conflict,
+                });
+            }
+            Err(e) => return Err(e).wrap_err_with(|| format!("couldn't edit {title}")),
+        };
+        let edit = &resp["edit"];
+        if edit["result"] != "Success" {
+            eyre::bail!("editing {title} failed: {edit}");
+        }
+        Ok(if edit.get("nochange").is_some() {
+            Outcome::Unchanged
+        } else if edit.get("new").is_some() {
+            Outcome::Created
+        } else {
+            Outcome::Updated
+        })
+    }
+}
@@ -0,0 +1,528 @@
+//! Template calls in wikitext, like `{{Structurebox | name = Sorter | prefab_hash = -1009150565}}`.
+//!
+//! Parsing keeps the exact text of every part, so a [`Template`] displays as the text it was parsed from, and
+//! [`Template::set`] only changes the value it sets. Generated templates are built with [`Template::new`] and written
+//! with [`Template::render`], which escapes values and keeps parameters in the order they were first set.
+
+use std::{fmt, ops::Range};
+
+use color_eyre::eyre;
+
+/// A parameter of a template call, as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Param {
+    /// Text before the `=` of a named parameter, `None` for positional ones
+    name: Option<String>,
+    value: String,
+}
+
+/// A template call, with its parameters in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    /// Text between `{{` and the first `|`
+    name: String,
+    params: Vec<Param>,
+}
+
+/// How [`Template::render`] lays out the parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// `{{Name|key=value|value}}`
+    Compact,
+    /// `{{Name |key = value |value}}`
+    Spaced,
+    /// A line per parameter, `{{Name\n| key = value\n|value\n}}`
+    Pretty,
+}
+
+/// Byte offsets in `s` of `c` where it's not inside a nested template, link or comment.
+fn top_level(s: &str, c: char) -> Vec<usize> {
+    let mut found = vec![];
+    let (mut braces, mut links) = (0usize, 0usize);
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        let skip = if rest.starts_with("<!--") {
+            rest.find("-->").map_or(rest.len(), |end| end + 3)
+        } else if rest.starts_with("{{") {
+            braces += 1;
+            2
+        } else if rest.starts_with("}}") && braces > 0 {
+            braces -= 1;
+            2
+        } else if rest.starts_with("[[") {
+            links += 1;
+            2
+        } else if rest.starts_with("]]") && links > 0 {
+            links -= 1;
+            2
+        } else {
+            let ch = rest.chars().next().map_or(1, char::len_utf8);
+            if braces == 0 && links == 0 && rest.starts_with(c) {
+                found.push(i);
+            }
+            ch
+        };
+        i += skip;
+    }
+    found
+}
+
+/// End of the template call starting at `start` in `text`, just after its matching `}}`.
+fn call_end(text: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < text.len() {
+        let rest = &text[i..];
+        if rest.starts_with("<!--") {
+            i += rest.find("-->")? + 3;
+        } else if rest.starts_with("{{") {
+            depth += 1;
+            i += 2;
+        } else if rest.starts_with("}}") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return Some(i);
+            }
+        } else {
+            i += rest.chars().next()?.len_utf8();
+        }
+    }
+    None
+}
+
+/// Whitespace at the start of `s`.
+fn leading(s: &str) -> String {
+    s[..s.len() - s.trim_start().len()].to_owned()
+}
+
+/// Whitespace at the end of `s`.
+fn trailing(s: &str) -> String {
+    s[s.trim_end().len()..].to_owned()
+}
+
+/// Where the whitespace and comments at the end of `value` start.
+fn trivia_start(value: &str) -> usize {
+    let mut end = value.trim_end().len();
+    while value[..end].ends_with("-->") {
+        let Some(start) = value[..end].rfind("<!--") else {
+            break;
+        };
+        end = value[..start].trim_end().len();
+    }
+    end
+}
+
+/// The string that's in `all` most often, the first of them on a tie.
+fn most_common(all: impl Iterator<Item = String>) -> Option<String> {
+    let mut counts = Vec::<(String, usize)>::new();
+    for s in all {
+        match counts.iter_mut().find(|(seen, _)| *seen == s) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((s, 1)),
+        }
+    }
+    let max = counts.iter().map(|(_, n)| *n).max()?;
+    counts.into_iter().find(|(_, n)| *n == max).map(|(s, _)| s)
+}
+
+/// Replace the `{{!}}` and `{{=}}` escapes in a parameter value with the characters they stand for.
+pub fn unescape(value: &str) -> String {
+    value.replace("{{!}}", "|").replace("{{=}}", "=")
+}
+
+/// Escape the `|` in `value` that would end the parameter as `{{!}}`, and for positional values the `=` that would
+/// name it as `{{=}}`.
+///
+/// Characters inside nested templates, links and comments are left alone, they don't end the parameter.
+fn escape(value: &str, positional: bool) -> String {
+    let mut at = top_level(value, '|');
+    if positional {
+        at.extend(top_level(value, '='));
+        at.sort_unstable();
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut from = 0;
+    for i in at {
+        out.push_str(&value[from..i]);
+        out.push_str(if value[i..].starts_with('|') {
+            "{{!}}"
+        } else {
+            "{{=}}"
+        });
+        from = i + 1;
+    }
+    out.push_str(&value[from..]);
+    out
+}
+
+/// The first call of the template `name` in `text`, at any depth, with its byte range.
+pub fn find(text: &str, name: &str) -> Option<(Range<usize>, Template)> {
+    text.match_indices("{{").find_map(|(start, _)| {
+        // `{{{1}}}` is a template parameter, not a call
+        if text[start..].starts_with("{{{") {
+            return None;
+        }
+        let end = call_end(text, start)?;
+        let template = Template::parse(&text[start..end]).ok()?;
+        (template.name() == name).then_some((start..end, template))
+    })
+}
+
+impl Template {
+    /// A call of the template `name` without parameters, add them with [`Template::param`] and
+    /// [`Template::positional`].
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            params: vec![],
+        }
+    }
+
+    /// Set the named parameter `key` to `value`, escaping `|` in it. Setting it again replaces it where it was.
+    pub fn param(&mut self, key: &str, value: impl fmt::Display) -> &mut Self {
+        let param = Param {
+            name: Some(key.to_owned()),
+            value: escape(&value.to_string(), false),
+        };
+        match self
+            .params
+            .iter()
+            .rposition(|p| p.name.as_deref() == Some(key))
+        {
+            Some(i) => self.params[i] = param,
+            None => self.params.push(param),
+        }
+        self
+    }
+
+    /// Add a positional parameter, escaping `|` and `=` in `value`.
+    pub fn positional(&mut self, value: impl fmt::Display) -> &mut Self {
+        self.params.push(Param {
+            name: None,
+            value: escape(&value.to_string(), true),
+        });
+        self
+    }
+
+    /// Parse a single template call, `text` has to start with its `{{` and end with its `}}`.
+    pub fn parse(text: &str) -> color_eyre::Result<Self> {
+        if !text.starts_with("{{") || call_end(text, 0) != Some(text.len()) {
+            eyre::bail!("not a single template call: `{text}`");
+        }
+        let inner = &text[2..text.len() - 2];
+        let mut parts = vec![];
+        let mut from = 0;
+        for pipe in top_level(inner, '|') {
+            parts.push(&inner[from..pipe]);
+            from = pipe + 1;
+        }
+        parts.push(&inner[from..]);
+        let name = parts.remove(0).to_owned();
+        let params = parts
+            .into_iter()
+            .map(|part| match top_level(part, '=').first() {
+                Some(&eq) => Param {
+                    name: Some(part[..eq].to_owned()),
+                    value: part[eq + 1..].to_owned(),
+                },
+                None => Param {
+                    name: None,
+                    value: part.to_owned(),
+                },
+            })
+            .collect();
+        Ok(Self { name, params })
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.trim()
+    }
+
+    /// Parameters by name, positional ones are named by their position starting at `1`.
+    ///
+    /// Values of named parameters are trimmed like MediaWiki does, positional ones are kept as written.
+    pub fn params(&self) -> impl Iterator<Item = (String, &str)> {
+        let mut position = 0;
+        self.params.iter().map(move |p| match &p.name {
+            Some(name) => (name.trim().to_owned(), p.value.trim()),
+            None => {
+                position += 1;
+                (position.to_string(), p.value.as_str())
+            }
+        })
+    }
+
+    /// Value of the parameter `key`, see [`Template::params`].
+    pub fn get(&self, key: &str) -> Option<&str> {
+        // a later parameter with the same name wins
+        self.params()
+            .filter(|(name, _)| name == key)
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    /// Set the parameter `key` to `value`, keeping the whitespace and comments around the old value and escaping `|`
+    /// in `value`.
+    ///
+    /// An empty value is spaced from the `=` like the other values. A new parameter is added at the end, spaced like
+    /// most of the others, and the whitespace before the `}}` stays where it was.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let value = &escape(value, false);
+        let named = self
+            .params
+            .iter()
+            .filter_map(|p| Some((p.name.as_deref()?, p.value.as_str())))
+            .collect::<Vec<_>>();
+        let value_before = most_common(
+            named
+                .iter()
+                .filter(|(_, v)| !v.trim().is_empty())
+                .map(|(_, v)| leading(v)),
+        );
+        let keys = self.params().map(|(name, _)| name).collect::<Vec<_>>();
+        if let Some(i) = keys.iter().rposition(|name| name == key) {
+            let param = &mut self.params[i];
+            let Some(name) = &param.name else {
+                param.value = escape(value, true);
+                return;
+            };
+            let old = &param.value;
+            let end = trivia_start(old);
+            let start = leading(old).len().min(end);
+            param.value = if start < end {
+                format!("{}{value}{}", &old[..start], &old[end..])
+            } else {
+                // nothing there yet, put the value right after the `=` instead of after a line break
+                let gap = old.len() - old.trim_start_matches([' ', '\t']).len();
+                let before = match (gap, value_before) {
+                    (0, Some(before)) if !before.contains('\n') => before,
+                    (0, _) => name[name.trim_end_matches([' ', '\t']).len()..].to_owned(),
+                    _ => old[..gap].to_owned(),
+                };
+                format!("{before}{value}{}", &old[gap..])
+            };
+            return;
+        }
+
+        let name_before = most_common(named.iter().map(|(n, _)| leading(n)));
+        let name_after = most_common(named.iter().map(|(n, _)| trailing(n)));
+        let between = most_common(
+            named
+                .iter()
+                .take(named.len().saturating_sub(1))
+                .map(|(_, v)| trailing(v)),
+        );
+        let mut value = format!("{}{value}", value_before.unwrap_or_default());
+        if let Some(Param {
+            name: Some(_),
+            value: last,
+        }) = self.params.last_mut()
+        {
+            // the whitespace after the last value is what's before the `}}`, the new value takes it over and the last
+            // one gets what's between the others
+            let closing = trailing(last);
+            let closing = closing
+                .find('\n')
+                .map_or(&*closing, |i| &closing[i..])
+                .to_owned();
+            last.truncate(last.trim_end().len());
+            last.push_str(between.as_deref().unwrap_or(&closing));
+            value.push_str(&closing);
+        }
+        self.params.push(Param {
+            name: Some(format!(
+                "{}{key}{}",
+                name_before.unwrap_or_default(),
+                name_after.unwrap_or_default()
+            )),
+            value,
+        });
+    }
+
+    /// Remove the named parameter `key`, every time it's given. The whitespace before the `}}` stays where it was.
+    pub fn remove(&mut self, key: &str) {
+        while let Some(i) = self
+            .params
+            .iter()
+            .rposition(|p| p.name.as_deref().is_some_and(|name| name.trim() == key))
+        {
+            let removed = self.params.remove(i);
+            if i < self.params.len() {
+                continue;
+            }
+            if let Some(Param {
+                name: Some(_),
+                value: last,
+            }) = self.params.last_mut()
+            {
+                last.truncate(last.trim_end().len());
+                last.push_str(&trailing(&removed.value));
+            }
+        }
+    }
+
+    /// The call with its parameters laid out as `layout`, the names and values are written as they were set.
+    pub fn render(&self, layout: Layout) -> String {
+        let (pipe, equals) = match layout {
+            Layout::Compact => ("|", "="),
+            Layout::Spaced => (" |", " = "),
+            Layout::Pretty => ("\n| ", " = "),
+        };
+        let mut out = format!("{{{{{}", self.name);
+        for param in &self.params {
+            // positional values start right after the `|`, their whitespace isn't trimmed
+            match &param.name {
+                Some(name) => out.push_str(&format!("{pipe}{name}{equals}{}", param.value)),
+                None => out.push_str(&format!("{}{}", pipe.trim_end_matches(' '), param.value)),
+            }
+        }
+        if layout == Layout::Pretty {
+            out.push('\n');
+        }
+        out.push_str("}}");
+        out
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{{{}", self.name)?;
+        for param in &self.params {
+            match &param.name {
+                Some(name) => write!(f, "|{name}={}", param.value)?,
+                None => write!(f, "|{}", param.value)?,
+            }
+        }
+        f.write_str("}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(text: &str, key: &str, value: &str) -> String {
+        let mut template = Template::parse(text).unwrap();
+        template.set(key, value);
+        template.to_string()
+    }
+
+    #[test]
+    fn round_trip() {
+        for text in [
+            "{{Structurebox\n| name = Sorter\n| prefab_hash = -1009150565\n}}",
+            "{{Recipe|{{Recipe/row |machine = Autolathe |mats = {{Cost|1|[[Iron]]}} }}}}",
+            "{{Itembox | image = [[File:Sorter.png|thumb|A sorter]] }}",
+            "{{Data Parameters/row|Open|Boolean|a {{!}} b|x{{=}}1|multiple=2}}",
+            "{{Itembox\n| name = Sorter <!-- a | comment = here -->\n}}",
+            "{{Slots|1|2| three |}}",
+        ] {
+            assert_eq!(Template::parse(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let template =
+            Template::parse("{{ Row |Open| a {{!}} b |x{{=}}1|w = {{Cost|a=1}} |[[Iron|iron]]|}}")
+                .unwrap();
+        assert_eq!(template.name(), "Row");
+        let params = template.params().collect::<Vec<_>>();
+        assert_eq!(
+            params,
+            [
+                ("1".to_owned(), "Open"),
+                ("2".to_owned(), " a {{!}} b "),
+                ("3".to_owned(), "x{{=}}1"),
+                ("w".to_owned(), "{{Cost|a=1}}"),
+                ("4".to_owned(), "[[Iron|iron]]"),
+                ("5".to_owned(), ""),
+            ]
+        );
+        assert_eq!(unescape(template.get("2").unwrap()), " a | b ");
+        assert!(Template::parse("{{Row|a}} {{Row|b}}").is_err());
+    }
+
+    #[test]
+    fn find_nested() {
+        let text = "intro {{Recipe|{{Recipe/row|machine=Autolathe}}}} outro";
+        let (range, template) = find(text, "Recipe/row").unwrap();
+        assert_eq!(&text[range], "{{Recipe/row|machine=Autolathe}}");
+        assert_eq!(template.get("machine"), Some("Autolathe"));
+        assert!(find("{{{1}}}", "1").is_none());
+    }
+
+    #[test]
+    fn set_existing() {
+        let text =
+            "{{Structurebox\n| name = Sorter <!-- a | comment -->\n| empty =\n| power = 10W\n}}";
+        assert_eq!(
+            set(text, "name", "Big Sorter"),
+            "{{Structurebox\n| name = Big Sorter <!-- a | comment -->\n| empty =\n| power = 10W\n}}"
+        );
+        assert_eq!(
+            set(text, "empty", "x"),
+            "{{Structurebox\n| name = Sorter <!-- a | comment -->\n| empty = x\n| power = 10W\n}}"
+        );
+        assert_eq!(set("{{X|a=1|b=}}", "b", "2"), "{{X|a=1|b=2}}");
+        assert_eq!(
+            set("{{X |a = 1 |b = 2}}", "a", "x|y"),
+            "{{X |a = x{{!}}y |b = 2}}"
+        );
+        assert_eq!(set("{{X|one|two}}", "2", "a=b"), "{{X|one|a{{=}}b}}");
+    }
+
+    #[test]
+    fn set_new() {
+        assert_eq!(
+            set("{{X\n| a = 1\n| b = 2\n}}", "c", "3"),
+            "{{X\n| a = 1\n| b = 2\n| c = 3\n}}"
+        );
+        // the last value being empty or oddly spaced doesn't matter
+        assert_eq!(
+            set("{{X\n| a = 1\n| b = 2\n| empty =\n}}", "c", "3"),
+            "{{X\n| a = 1\n| b = 2\n| empty =\n| c = 3\n}}"
+        );
+        assert_eq!(
+            set("{{X\n| a = 1\n| b = 2\n| c =    4  \n}}", "d", "3"),
+            "{{X\n| a = 1\n| b = 2\n| c =    4\n| d = 3\n}}"
+        );
+        assert_eq!(
+            set("{{X |a = 1 |b = 2}}", "c", "3"),
+            "{{X |a = 1 |b = 2 |c = 3}}"
+        );
+        assert_eq!(set("{{X|a=1}}", "b", "2"), "{{X|a=1|b=2}}");
+        assert_eq!(set("{{X|one}}", "b", "2"), "{{X|one|b=2}}");
+        assert_eq!(set("{{X}}", "b", "2"), "{{X|b=2}}");
+    }
+
+    #[test]
+    fn remove() {
+        let remove = |text: &str, key: &str| {
+            let mut template = Template::parse(text).unwrap();
+            template.remove(key);
+            template.to_string()
+        };
+        assert_eq!(
+            remove("{{X\n| a = 1\n| b = 2 <!-- old -->\n| c = 3\n}}", "b"),
+            "{{X\n| a = 1\n| c = 3\n}}"
+        );
+        assert_eq!(remove("{{X\n| a = 1\n| b = 2\n}}", "b"), "{{X\n| a = 1\n}}");
+        assert_eq!(remove("{{X |a = 1 |b = 2 |a = 3}}", "a"), "{{X |b = 2}}");
+        assert_eq!(remove("{{X|one|1=two}}", "1"), "{{X|one}}");
+    }
+
+    #[test]
+    fn render() {
+        let mut template = Template::new("X");
+        template.param("a", "1|2").positional("b=c").param("a", 3);
+        assert_eq!(template.render(Layout::Compact), "{{X|a=3|b{{=}}c}}");
+        assert_eq!(template.render(Layout::Spaced), "{{X |a = 3 |b{{=}}c}}");
+        assert_eq!(
+            template.render(Layout::Pretty),
+            "{{X\n| a = 3\n|b{{=}}c\n}}"
+        );
+    }
+}
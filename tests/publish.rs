@@ -0,0 +1,304 @@
+#![cfg(feature = "mw")]
+//! `publish` against a mock of the MediaWiki action API, run with `cargo test --features mw`.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    process::{Command, Output},
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{json, Value};
+
+/// Pages on the mock wiki, with a revision number standing in for timestamps.
+#[derive(Default)]
+struct State {
+    pages: BTreeMap<String, (String, u32)>,
+    /// Pages that someone else edits right after they're fetched
+    busy: Vec<String>,
+    clock: u32,
+    edits: Vec<BTreeMap<String, String>>,
+}
+
+fn timestamp(n: u32) -> String {
+    format!("2024-01-01T00:{:02}:{:02}Z", n / 60, n % 60)
+}
+
+fn error(code: &str) -> Value {
+    json!({"errors": [{"code": code, "text": code}]})
+}
+
+impl State {
+    fn handle(&mut self, params: BTreeMap<String, String>) -> Value {
+        let get = |k: &str| params.get(k).map(String::as_str);
+        self.clock += 1;
+        match (get("action"), get("meta"), get("prop")) {
+            (Some("query"), Some("tokens"), _) => {
+                let ty = get("type").unwrap_or("csrf");
+                json!({"query": {"tokens": {format!("{ty}token"): format!("{ty}+\\")}}})
+            }
+            (Some("login"), ..) => {
+                if get("lgname") == Some("Bot@test") && get("lgpassword") == Some("secret") {
+                    json!({"login": {"result": "Success"}})
+                } else {
+                    json!({"login": {"result": "Failed", "reason": {"code": "wrongpassword", "text": "wrong"}}})
+                }
+            }
+            (Some("query"), _, Some("revisions")) => {
+                let title = get("titles").unwrap().to_owned();
+                let page = match self.pages.get(&title) {
+                    Some((text, rev)) => json!({"title": title, "revisions": [{
+                        "timestamp": timestamp(*rev),
+                        "slots": {"main": {"content": text}},
+                    }]}),
+                    None => json!({"title": title, "missing": true}),
+                };
+                let resp =
+                    json!({"curtimestamp": timestamp(self.clock), "query": {"pages": [page]}});
+                if self.busy.contains(&title) {
+                    self.clock += 1;
+                    self.pages
+                        .insert(title, ("someone else's edit".to_owned(), self.clock));
+                }
+                resp
+            }
+            (Some("edit"), ..) => self.edit(params),
+            _ => error("badrequest"),
+        }
+    }
+
+    fn edit(&mut self, params: BTreeMap<String, String>) -> Value {
+        self.edits.push(params.clone());
+        let get = |k: &str| params.get(k).map(String::as_str);
+        if get("token") != Some("csrf+\\") {
+            return error("badtoken");
+        }
+        let title = get("title").unwrap();
+        let text = get("text").unwrap();
+        let current = self.pages.get(title);
+        match current {
+            Some(_) if params.contains_key("createonly") => return error("articleexists"),
+            None if params.contains_key("basetimestamp") => return error("pagedeleted"),
+            None if params.contains_key("nocreate") => return error("missingtitle"),
+            Some((_, rev)) if get("basetimestamp").is_some_and(|base| base != timestamp(*rev)) => {
+                return error("editconflict")
+            }
+            Some((current, _)) if current.trim_end() == text.trim_end() => {
+                return json!({"edit": {"result": "Success", "title": title, "nochange": true}})
+            }
+            _ => {}
+        }
+        let new = current.is_none();
+        self.pages
+            .insert(title.to_owned(), (text.to_owned(), self.clock));
+        let mut edit = json!({"result": "Success", "title": title});
+        if new {
+            edit["new"] = json!(true);
+        }
+        json!({ "edit": edit })
+    }
+}
+
+fn decode(s: &str) -> String {
+    let mut bytes = vec![];
+    let mut it = s.bytes();
+    while let Some(b) = it.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [it.next().unwrap(), it.next().unwrap()];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).unwrap(), 16).unwrap());
+            }
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).unwrap()
+}
+
+fn serve(mut stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request = String::new();
+    reader.read_line(&mut request).unwrap();
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    let path = request.split_whitespace().nth(1).unwrap();
+    let query = path.split_once('?').map_or("", |(_, q)| q);
+    let params = [query, std::str::from_utf8(&body).unwrap()]
+        .iter()
+        .flat_map(|s| s.split('&'))
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (decode(k), decode(v)))
+        .collect();
+    let body = state.lock().unwrap().handle(params).to_string();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+}
+
+/// Serve `state` as a mock wiki in the background, returning its api url.
+fn mock_wiki(state: State) -> (String, Arc<Mutex<State>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api.php", listener.local_addr().unwrap());
+    let state = Arc::new(Mutex::new(state));
+    let shared = state.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            serve(stream.unwrap(), &shared);
+        }
+    });
+    (url, state)
+}
+
+fn wiki_with(pages: &[(&str, &str)], busy: &[&str]) -> State {
+    State {
+        pages: pages
+            .iter()
+            .map(|(title, text)| (title.to_string(), (text.to_string(), 0)))
+            .collect(),
+        busy: busy.iter().map(|s| s.to_string()).collect(),
+        ..State::default()
+    }
+}
+
+/// Write `files` to a fresh directory and publish it to a mock wiki with `state`.
+fn publish(
+    name: &str,
+    state: State,
+    files: &[(&str, &str)],
+    mode: &str,
+) -> (Output, Arc<Mutex<State>>) {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (title, text) in files {
+        std::fs::write(dir.join(format!("{title}.wiki")), text).unwrap();
+    }
+    let (url, state) = mock_wiki(state);
+    let output = Command::new(env!("CARGO_BIN_EXE_wiki-dumper"))
+        .args([
+            "publish",
+            "--mode",
+            mode,
+            "--summary",
+            "test",
+            "--api-url",
+            &url,
+        ])
+        .arg(&dir)
+        .env("WIKI_DUMPER_USERNAME", "Bot@test")
+        .env("WIKI_DUMPER_PASSWORD", "secret")
+        .output()
+        .unwrap();
+    (output, state)
+}
+
+fn text(state: &Mutex<State>, title: &str) -> Option<String> {
+    state
+        .lock()
+        .unwrap()
+        .pages
+        .get(title)
+        .map(|(text, _)| text.clone())
+}
+
+const FILES: &[(&str, &str)] = &[
+    ("Existing", "new text"),
+    ("New", "new page"),
+    ("Same", "same text\n"),
+];
+const PAGES: &[(&str, &str)] = &[("Existing", "old text"), ("Same", "same text")];
+
+#[test]
+fn create_only() {
+    let (output, state) = publish("create_only", wiki_with(PAGES, &[]), FILES, "create-only");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        stdout,
+        "skipped   Existing\ncreated   New\nskipped   Same\n"
+    );
+    assert_eq!(text(&state, "Existing").as_deref(), Some("old text"));
+    assert_eq!(text(&state, "New").as_deref(), Some("new page"));
+}
+
+#[test]
+fn update_only() {
+    let (output, state) = publish("update_only", wiki_with(PAGES, &[]), FILES, "update-only");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        stdout,
+        "updated   Existing\nskipped   New\nunchanged Same\n"
+    );
+    assert_eq!(text(&state, "Existing").as_deref(), Some("new text"));
+    assert_eq!(text(&state, "New"), None);
+    let state = state.lock().unwrap();
+    let [edit] = state.edits.as_slice() else {
+        panic!("expected one edit, got {:?}", state.edits);
+    };
+    assert_eq!(edit["basetimestamp"], timestamp(0));
+    assert!(edit.contains_key("starttimestamp") && edit.contains_key("nocreate"));
+}
+
+#[test]
+fn overwrite() {
+    let (output, state) = publish("overwrite", wiki_with(PAGES, &[]), FILES, "overwrite");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        stdout,
+        "updated   Existing\ncreated   New\nunchanged Same\n"
+    );
+    assert_eq!(text(&state, "Existing").as_deref(), Some("new text"));
+    assert_eq!(text(&state, "New").as_deref(), Some("new page"));
+}
+
+#[test]
+fn edit_conflict() {
+    let (output, state) = publish(
+        "edit_conflict",
+        wiki_with(PAGES, &["Existing"]),
+        FILES,
+        "overwrite",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success());
+    assert_eq!(
+        stdout,
+        "conflict  Existing\ncreated   New\nunchanged Same\n"
+    );
+    assert_eq!(
+        text(&state, "Existing").as_deref(),
+        Some("someone else's edit")
+    );
+}